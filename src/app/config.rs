@@ -0,0 +1,13 @@
+use clap::{Args, Subcommand};
+
+#[derive(Args, Debug)]
+pub struct ConfigArgs {
+    #[command(subcommand)]
+    pub action: ConfigAction,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigAction {
+    /// Prints the merged settings of config file and defaults.
+    Show,
+}
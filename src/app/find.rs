@@ -2,6 +2,8 @@ use std::path::PathBuf;
 
 use clap::{Args, Subcommand};
 
+use crate::config::Config;
+
 #[derive(Args, Debug)]
 pub struct FindArgs {
     #[command(subcommand)]
@@ -12,6 +14,17 @@ pub struct FindArgs {
     pub dir: Option<PathBuf>,
 }
 
+impl FindArgs {
+    /// Overrides the config values with all flags set on the command line.
+    pub fn merge(&self, mut config: Config) -> Config {
+        if let Some(dir) = &self.dir {
+            config.log.dir = Some(dir.clone());
+        }
+
+        config
+    }
+}
+
 #[derive(Subcommand, Debug)]
 pub enum FindAction {
     Longest,
@@ -0,0 +1,36 @@
+use std::path::PathBuf;
+
+use clap::Args;
+
+use crate::config::Config;
+
+#[derive(Args, Debug)]
+pub struct ImportArgs {
+    /// Logfiles to import, plain or compressed. Without any, all logfiles from the log directory
+    /// are imported.
+    #[arg(value_name = "FILE")]
+    pub files: Vec<PathBuf>,
+
+    /// (Optional) Sets the SQLite database. A relative path is resolved against the log
+    /// directory. [default: database of the config]
+    #[arg(long, value_name = "FILE")]
+    pub database: Option<PathBuf>,
+
+    /// (Optional) Sets log directory.
+    #[arg(short, long, value_enum)]
+    pub dir: Option<PathBuf>,
+}
+
+impl ImportArgs {
+    /// Overrides the config values with all flags set on the command line.
+    pub fn merge(&self, mut config: Config) -> Config {
+        if let Some(database) = &self.database {
+            config.log.database = Some(database.clone());
+        }
+        if let Some(dir) = &self.dir {
+            config.log.dir = Some(dir.clone());
+        }
+
+        config
+    }
+}
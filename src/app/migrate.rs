@@ -0,0 +1,32 @@
+use std::path::PathBuf;
+
+use clap::Args;
+
+use crate::config::Config;
+
+#[derive(Args, Debug)]
+pub struct MigrateArgs {
+    /// Logfiles to migrate, plain or compressed. Without any, all logfiles from the log directory
+    /// are migrated.
+    #[arg(value_name = "FILE")]
+    pub files: Vec<PathBuf>,
+
+    /// Flag: Only prints how many entries would be migrated.
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// (Optional) Sets log directory.
+    #[arg(short, long, value_enum)]
+    pub dir: Option<PathBuf>,
+}
+
+impl MigrateArgs {
+    /// Overrides the config values with all flags set on the command line.
+    pub fn merge(&self, mut config: Config) -> Config {
+        if let Some(dir) = &self.dir {
+            config.log.dir = Some(dir.clone());
+        }
+
+        config
+    }
+}
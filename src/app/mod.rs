@@ -1,21 +1,37 @@
+use std::path::PathBuf;
+
 use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
 
 use crate::{
-    app::{find::FindArgs, monitor::MonitorArgs, report::ReportArgs},
+    app::{
+        config::ConfigArgs, find::FindArgs, import::ImportArgs, migrate::MigrateArgs,
+        monitor::MonitorArgs, prune::PruneArgs, report::ReportArgs, service::InstallServiceArgs,
+    },
+    config::{Config, run as config_run},
     find::run as find_run,
+    import::run as import_run,
+    migrate::run as migrate_run,
     monitor::run as monitor_run,
     project::Project,
+    prune::run as prune_run,
     report::run as report_run,
+    systemd::install as install_service,
 };
 
+pub(super) mod config;
 pub(super) mod find;
+pub(super) mod import;
+pub(super) mod migrate;
 pub(super) mod monitor;
+pub(super) mod prune;
 pub(super) mod report;
+pub(super) mod service;
 pub(super) mod shared;
 
 pub struct App {
     project: Project,
+    config: Config,
     cli: Cli,
 }
 
@@ -23,21 +39,69 @@ impl App {
     pub fn new() -> Result<Self> {
         let cli = Cli::parse();
         let project = Project::new()?;
+        let config = Config::load(cli.config.as_deref(), &project)?;
 
-        Ok(App { cli, project })
+        Ok(App {
+            cli,
+            project,
+            config,
+        })
     }
 
     pub async fn run(self) -> Result<()> {
         match self.cli.command {
-            Command::Monitor(args) => monitor_run(args, self.project)
+            Command::Monitor(args) => monitor_run(*args, self.config, self.project)
                 .await
                 .context("The monitor command failed"),
-            Command::Report(args) => report_run(args, self.project)
-                .await
-                .context("The report command failed"),
-            Command::Find(args) => find_run(args, self.project),
+            Command::Report(args) => {
+                let config = args.merge(self.config);
+
+                report_run(args, config, self.project)
+                    .await
+                    .context("The report command failed")
+            }
+            Command::Find(args) => {
+                let config = args.merge(self.config);
+
+                find_run(args, config, self.project)
+            }
+            Command::Prune(args) => {
+                let config = args.merge(self.config);
+
+                prune_run(args, config, self.project).context("The prune command failed")
+            }
+            Command::Import(args) => {
+                let config = args.merge(self.config);
+
+                import_run(args, config, self.project).context("The import command failed")
+            }
+            Command::Migrate(args) => {
+                let config = args.merge(self.config);
+
+                migrate_run(args, config, self.project).context("The migrate command failed")
+            }
+            Command::Config(args) => config_run(args, self.config),
+            Command::InstallService(args) => {
+                Self::check_monitor_args(&args.monitor_args)?;
+
+                install_service(args, self.cli.config.as_deref())
+                    .context("The install-service command failed")
+            }
         }
     }
+
+    /// Parses the arguments the service passes to the monitor, so mistakes show up right away
+    /// and not only once the service starts.
+    fn check_monitor_args(monitor_args: &[String]) -> Result<()> {
+        let args = ["netcheck", "monitor"]
+            .into_iter()
+            .map(String::from)
+            .chain(monitor_args.iter().cloned());
+
+        Cli::try_parse_from(args)
+            .map(|_| ())
+            .map_err(|err| anyhow::anyhow!("Invalid monitor arguments: {err}"))
+    }
 }
 
 #[derive(Parser, Debug)]
@@ -45,11 +109,20 @@ impl App {
 struct Cli {
     #[command(subcommand)]
     command: Command,
+
+    /// (Optional) Sets the config file. Defaults to 'netcheck.toml' in the config directory.
+    #[arg(long, global = true, value_name = "FILE")]
+    config: Option<PathBuf>,
 }
 
 #[derive(Subcommand, Debug)]
 enum Command {
-    Monitor(MonitorArgs),
+    Monitor(Box<MonitorArgs>),
     Report(ReportArgs),
     Find(FindArgs),
+    Prune(PruneArgs),
+    Import(ImportArgs),
+    Migrate(MigrateArgs),
+    Config(ConfigArgs),
+    InstallService(InstallServiceArgs),
 }
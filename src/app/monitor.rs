@@ -1,13 +1,19 @@
-use std::path::PathBuf;
+use std::{net::SocketAddr, path::PathBuf};
 
-use clap::Args;
+use clap::{Args, ValueEnum};
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    log::{DEFAULT_FILE_PREFIX, DEFAULT_LOG_MODE, DEFAULT_MAX_SIZE, LogMode},
-    monitor::{DEFAULT_MONITOR_EXCLUDE_STOPPED, DEFAULT_MONITOR_INTERVAL, DEFAULT_MONITOR_TIMEOUT},
+    app::shared::DebounceArgs,
+    archive::Compression,
+    config::Config,
+    log::{ConsoleFormat, DEFAULT_FILE_PREFIX, LogMode},
+    model::CheckTarget,
 };
 
-#[derive(Args, Debug)]
+pub const DEFAULT_RECORD_MODE: RecordMode = RecordMode::Transitions;
+
+#[derive(Args, Debug, Clone)]
 pub struct MonitorArgs {
     #[command(flatten)]
     pub logger: LoggerArgs,
@@ -16,24 +22,78 @@ pub struct MonitorArgs {
     pub observer: ObserverArgs,
 }
 
-#[derive(clap::Args, Debug)]
+impl MonitorArgs {
+    /// Overrides the config values with all flags set on the command line.
+    pub fn merge(self, mut config: Config) -> Config {
+        self.logger.merge(&mut config);
+        self.observer.merge(&mut config);
+
+        config
+    }
+}
+
+#[derive(clap::Args, Debug, Clone)]
 pub struct LoggerArgs {
     /// Sets the logfile name. The logger automatically appends the timestamp and an index to log
-    /// file name.
-    #[arg(short, long, default_value = DEFAULT_FILE_PREFIX)]
-    pub filename: String,
+    /// file name. [default: netcheck]
+    #[arg(short, long)]
+    pub filename: Option<String>,
 
     /// Sets the max size of the logfile. If this value is exceeded, a new logfile will be created.
-    #[arg(short, long, default_value_t = DEFAULT_MAX_SIZE)]
-    pub size: u64,
+    /// [default: 2097152]
+    #[arg(short, long)]
+    pub size: Option<u64>,
 
     /// Sets the log mode. 'Stdout' will only log in the terminal, 'File' will only log into files.
-    #[arg(short, long, value_enum, default_value_t = DEFAULT_LOG_MODE)]
-    pub mode: LogMode,
+    /// 'Journald' and 'Syslog' log into files and send every entry to the journal or syslog
+    /// instead of the terminal. [default: all]
+    #[arg(short, long, value_enum)]
+    pub mode: Option<LogMode>,
+
+    /// Sets the format of the lines printed to the terminal. Logfiles are always written as JSONL.
+    /// [default: json]
+    #[arg(long, value_enum)]
+    pub console: Option<ConsoleFormat>,
+
+    /// Compresses logfiles once they have been rotated out. Reports read compressed logfiles as
+    /// well. [default: none]
+    #[arg(long, value_enum)]
+    pub compress: Option<Compression>,
 
     /// (Optional) Sets log directory.
     #[arg(short, long, value_enum)]
     pub dir: Option<PathBuf>,
+
+    /// (Optional) Stores the entries in this SQLite database as well. A relative path is resolved
+    /// against the log directory.
+    #[arg(long, value_name = "FILE")]
+    pub database: Option<PathBuf>,
+}
+
+impl LoggerArgs {
+    fn merge(self, config: &mut Config) {
+        if let Some(filename) = self.filename {
+            config.log.filename = filename;
+        }
+        if let Some(size) = self.size {
+            config.log.size = size;
+        }
+        if let Some(mode) = self.mode {
+            config.log.mode = mode;
+        }
+        if let Some(console) = self.console {
+            config.log.console = console;
+        }
+        if let Some(compress) = self.compress {
+            config.log.compress = compress;
+        }
+        if let Some(dir) = self.dir {
+            config.log.dir = Some(dir);
+        }
+        if let Some(database) = self.database {
+            config.log.database = Some(database);
+        }
+    }
 }
 
 #[derive(clap::Args, Debug)]
@@ -44,18 +104,153 @@ pub struct LoggerLocationArgs {
     pub filename: String,
 }
 
-#[derive(clap::Args, Debug)]
+#[derive(clap::Args, Debug, Clone)]
 pub struct ObserverArgs {
-    /// Sets the interval in which the connection checks will be performed.
-    #[arg(short, long, default_value_t = DEFAULT_MONITOR_INTERVAL)]
-    pub interval: u64,
+    /// Sets the interval in which the connection checks will be performed. [default: 5]
+    #[arg(short, long)]
+    pub interval: Option<u64>,
+
+    /// (Optional) Sets the interval while the connection is offline, e.g. to see more precisely
+    /// when it came back.
+    #[arg(long, value_name = "SECONDS")]
+    pub offline_interval: Option<u64>,
+
+    /// (Optional) Sets the interval while the connection is degraded.
+    #[arg(long, value_name = "SECONDS")]
+    pub degraded_interval: Option<u64>,
+
+    /// (Optional) Enables backoff while the connection is stable. The interval doubles after every
+    /// check up to this value.
+    #[arg(long, value_name = "SECONDS")]
+    pub max_interval: Option<u64>,
 
-    /// Sets the timeout for the requests that check the internet connection.
-    #[arg(short, long, default_value_t = DEFAULT_MONITOR_TIMEOUT)]
-    pub timeout: u64,
+    /// Sets the timeout for the requests that check the internet connection. [default: 3]
+    #[arg(short, long)]
+    pub timeout: Option<u64>,
+
+    /// Sets the average latency in milliseconds from which on a check is rated slow. Targets can
+    /// override it with the 'latency' option. [default: 500]
+    #[arg(long, value_name = "MS")]
+    pub latency_threshold: Option<u64>,
 
     /// Flag that disables the final connection check, if set. Otherwise, the tool will try to
     /// perform a last check on graceful shutdown (CTRL-C).
-    #[arg(long, default_value_t = DEFAULT_MONITOR_EXCLUDE_STOPPED)]
+    #[arg(long)]
     pub exclude_stopped: bool,
+
+    /// Flag that enables the final connection check again, if the config file disables it.
+    #[arg(long, conflicts_with = "exclude_stopped")]
+    pub no_exclude_stopped: bool,
+
+    /// Sets which checks are logged. 'Transitions' only logs the checks that change the
+    /// connectivity, 'Full' logs every check and 'Sample' every check as well, but only with the
+    /// failed targets. Every check is needed for the uptime report. [default: transitions]
+    #[arg(long, value_enum)]
+    pub record: Option<RecordMode>,
+
+    /// Sets the interval of the heartbeats, which tell the reports that the monitor was running,
+    /// even if no check was logged. 0 disables them. [default: 60]
+    #[arg(long, value_name = "SECONDS")]
+    pub heartbeat: Option<u64>,
+
+    /// Flag: Shows a live dashboard with the status of every target and the recent outages
+    /// instead of printing the log lines. Logfiles are still written.
+    #[arg(long)]
+    pub tui: bool,
+
+    /// Adds a check target in the form 'NAME=URL[,OPTION=VALUE...]'. Options are status, timeout,
+    /// latency and resolver. Can be used multiple times and replaces the targets from the config
+    /// file. Without any target, Google, Example and 1.1.1.1 are checked.
+    #[arg(long = "target", value_name = "TARGET")]
+    pub targets: Vec<CheckTarget>,
+
+    /// Sets the share of failed targets (0.0 - 1.0) from which on the connection counts as
+    /// degraded. [default: 0.5]
+    #[arg(long, value_name = "SHARE")]
+    pub degraded_share: Option<f64>,
+
+    /// Sets the average latency in milliseconds above which the connection counts as degraded.
+    /// Without a value, the connection is degraded whenever the check is rated slow.
+    #[arg(long, value_name = "MS")]
+    pub degraded_latency: Option<u64>,
+
+    #[command(flatten)]
+    pub debounce: DebounceArgs,
+
+    /// (Optional) Serves Prometheus metrics at '/metrics' on this address, e.g. 127.0.0.1:9898.
+    #[arg(long, value_name = "ADDR")]
+    pub metrics: Option<SocketAddr>,
+
+    /// (Optional) Serves the current status as JSON at '/status' on this address.
+    #[arg(long, value_name = "ADDR")]
+    pub status: Option<SocketAddr>,
+
+    /// (Optional) Serves the current status as JSON at '/status' on this Unix socket.
+    #[arg(long, value_name = "PATH")]
+    pub status_socket: Option<PathBuf>,
+}
+
+impl ObserverArgs {
+    fn merge(self, config: &mut Config) {
+        if let Some(interval) = self.interval {
+            config.monitor.interval = interval;
+        }
+        if let Some(interval) = self.offline_interval {
+            config.monitor.offline_interval = Some(interval);
+        }
+        if let Some(interval) = self.degraded_interval {
+            config.monitor.degraded_interval = Some(interval);
+        }
+        if let Some(interval) = self.max_interval {
+            config.monitor.max_interval = Some(interval);
+        }
+        if let Some(timeout) = self.timeout {
+            config.monitor.timeout = timeout;
+        }
+        if let Some(latency_threshold) = self.latency_threshold {
+            config.monitor.latency_threshold = latency_threshold;
+        }
+        if self.exclude_stopped {
+            config.monitor.exclude_stopped = true;
+        }
+        if self.no_exclude_stopped {
+            config.monitor.exclude_stopped = false;
+        }
+        if let Some(record) = self.record {
+            config.monitor.record = record;
+        }
+        if let Some(heartbeat) = self.heartbeat {
+            config.monitor.heartbeat = heartbeat;
+        }
+        if self.tui {
+            config.monitor.tui = true;
+        }
+        if !self.targets.is_empty() {
+            config.targets = self.targets;
+        }
+        if let Some(share) = self.degraded_share {
+            config.degraded.failed_share = share;
+        }
+        if let Some(latency) = self.degraded_latency {
+            config.degraded.latency = Some(latency);
+        }
+        self.debounce.merge(config);
+        if let Some(listen) = self.metrics {
+            config.metrics.listen = Some(listen);
+        }
+        if let Some(listen) = self.status {
+            config.status.listen = Some(listen);
+        }
+        if let Some(socket) = self.status_socket {
+            config.status.socket = Some(socket);
+        }
+    }
+}
+
+#[derive(ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordMode {
+    Transitions,
+    Full,
+    Sample,
 }
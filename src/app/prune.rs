@@ -0,0 +1,55 @@
+use std::path::PathBuf;
+
+use clap::Args;
+
+use crate::config::Config;
+
+#[derive(Args, Debug)]
+pub struct PruneArgs {
+    /// (Optional) Sets the days the logfiles are kept besides the current one.
+    #[arg(long, value_name = "DAYS")]
+    pub max_age: Option<u64>,
+
+    /// (Optional) Sets the bytes all logfiles may take up together.
+    #[arg(long, value_name = "BYTES")]
+    pub max_size: Option<u64>,
+
+    /// (Optional) Sets the number of logfiles that are kept. The newest logfile is always kept.
+    #[arg(long, value_name = "N")]
+    pub max_files: Option<usize>,
+
+    /// Flag: Writes a summary of every day before its logfiles are removed.
+    #[arg(long)]
+    pub summarize: bool,
+
+    /// Flag: Only prints which logfiles would be removed.
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// (Optional) Sets log directory.
+    #[arg(short, long, value_enum)]
+    pub dir: Option<PathBuf>,
+}
+
+impl PruneArgs {
+    /// Overrides the config values with all flags set on the command line.
+    pub fn merge(&self, mut config: Config) -> Config {
+        if let Some(max_age) = self.max_age {
+            config.retention.max_age = Some(max_age);
+        }
+        if let Some(max_size) = self.max_size {
+            config.retention.max_size = Some(max_size);
+        }
+        if let Some(max_files) = self.max_files {
+            config.retention.max_files = Some(max_files);
+        }
+        if self.summarize {
+            config.retention.summarize = true;
+        }
+        if let Some(dir) = &self.dir {
+            config.log.dir = Some(dir.clone());
+        }
+
+        config
+    }
+}
@@ -1,21 +1,31 @@
 use anyhow::Result;
+use chrono::NaiveDate;
 use clap::{Args, ValueEnum};
 use inquire::MultiSelect;
+use serde::{Deserialize, Serialize};
 use std::{
     collections::HashSet,
     fs::read_dir,
     path::{Path, PathBuf},
+    time::Duration,
 };
 
-use crate::{model::OutageLogPrecision, project::Project, sort::sort_by_filename_date};
+use crate::{
+    app::shared::DebounceArgs, archive, config::Config, database::Database,
+    sort::sort_by_filename_date, time::parse_duration,
+};
 
 pub const DEFAULT_REPORT_MODE: ReportMode = ReportMode::Outages;
 
 #[derive(clap::Args, Debug)]
 pub struct ReportArgs {
-    /// Defines reporting mode. Simple just prints a list of times with connectivity status.
-    #[arg(short, long, value_enum, default_value_t = DEFAULT_REPORT_MODE)]
-    pub mode: ReportMode,
+    /// Defines reporting mode. Simple just prints a list of times with connectivity status, Phases
+    /// breaks the latency of every target down into DNS, connect, TLS and first byte, Slow lists
+    /// periods in which the connection was up but slower than the latency threshold, Uptime sums
+    /// up the uptime and the latency percentiles, Cleanup removes the selected logfiles.
+    /// [default: outages]
+    #[arg(short, long, value_enum)]
+    pub mode: Option<ReportMode>,
 
     /// (Optional) Sets log directory.
     #[arg(short, long, value_enum)]
@@ -25,26 +35,86 @@ pub struct ReportArgs {
     #[arg(long, default_value_t = false)]
     exact: bool,
 
+    /// (Optional) Re-evaluates the logged checks with this latency threshold in milliseconds.
+    #[arg(long, value_name = "MS")]
+    latency_threshold: Option<u64>,
+
+    /// (Optional) Seconds without any log entry from which on the monitor counts as not running.
+    /// By default, three missed heartbeats count.
+    #[arg(long, value_name = "SECONDS")]
+    gap_threshold: Option<u64>,
+
+    #[command(flatten)]
+    debounce: DebounceArgs,
+
+    #[command(flatten)]
+    pub cleanup: CleanupArgs,
+
     #[command(flatten)]
     file_args: ReportFileArgs,
 }
 
+// Options of the cleanup mode. No doc comment, since clap would show it as the about of `report`.
+#[derive(Args, Debug)]
+pub struct CleanupArgs {
+    /// Flag: Only lists the logfiles the cleanup mode would remove.
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// (Optional) Only removes logfiles that were last written longer ago than this, e.g. '7d',
+    /// '12h' or '2w'.
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    pub older_than: Option<Duration>,
+
+    /// Flag: Removes the logfiles without asking for confirmation.
+    #[arg(short, long)]
+    pub yes: bool,
+}
+
 impl ReportArgs {
-    pub fn logfiles(&self, project: &Project) -> Result<Vec<PathBuf>> {
-        let logdir = match self.dir.as_deref() {
-            Some(p) => p,
-            None => project.log_dir(),
-        };
+    /// Logfiles to report on. Without a selection, `--older-than` of the cleanup mode picks from
+    /// every logfile in the log directory instead of only the newest one.
+    pub fn logfiles<P: AsRef<Path>>(&self, logdir: P, mode: &ReportMode) -> Result<Vec<PathBuf>> {
+        if matches!(mode, ReportMode::Cleanup)
+            && self.cleanup.older_than.is_some()
+            && matches!(self.file_args.strategy(), ReportFileStrategy::Default)
+        {
+            return ReportFileArgs::try_collect_from_logdir(logdir);
+        }
 
         self.file_args.logfiles(logdir)
     }
 
-    pub fn log_precision(&self) -> Option<OutageLogPrecision> {
+    /// Whether the report reads the database instead of the logfiles, which it does unless files
+    /// are given or are about to be removed.
+    pub fn reads_database(&self, mode: &ReportMode) -> bool {
+        self.file_args.files.is_empty() && !matches!(mode, ReportMode::Cleanup)
+    }
+
+    pub fn days(&self, database: &Database) -> Result<Vec<NaiveDate>> {
+        self.file_args.days(database)
+    }
+
+    /// Overrides the config values with all flags set on the command line.
+    pub fn merge(&self, mut config: Config) -> Config {
+        if let Some(mode) = &self.mode {
+            config.report.mode = mode.clone();
+        }
+        if let Some(dir) = &self.dir {
+            config.log.dir = Some(dir.clone());
+        }
         if self.exact {
-            Some(OutageLogPrecision::Exact)
-        } else {
-            None
+            config.report.exact = true;
         }
+        if let Some(latency_threshold) = self.latency_threshold {
+            config.report.latency_threshold = Some(latency_threshold);
+        }
+        if let Some(gap_threshold) = self.gap_threshold {
+            config.report.gap_threshold = Some(gap_threshold);
+        }
+        self.debounce.merge(&mut config);
+
+        config
     }
 }
 
@@ -55,15 +125,15 @@ struct ReportFileArgs {
     #[arg(value_name = "FILE")]
     files: Vec<PathBuf>,
 
-    /// Picks all available logfiles from the log directory.
+    /// Picks all available logfiles from the log directory, or all days from the database.
     #[arg(short, long)]
     all: bool,
 
-    /// Only pick the last N logfiles.
+    /// Only pick the last N logfiles, or the last N days from the database.
     #[arg(short, long, value_name = "N")]
     last: Option<usize>,
 
-    /// Enables interactive mode, where files can be selected via MultiSelect.
+    /// Enables interactive mode, where files or days can be selected via MultiSelect.
     #[arg(short, long)]
     interactive: bool,
 }
@@ -79,6 +149,24 @@ impl<'a> ReportFileArgs {
         })
     }
 
+    /// Local days to load from the database, the newest first. By default, only the last one.
+    pub fn days(&self, database: &Database) -> Result<Vec<NaiveDate>> {
+        let mut days = database.days()?;
+
+        Ok(match self.strategy() {
+            ReportFileStrategy::All => days,
+            ReportFileStrategy::Last(n) => {
+                days.truncate(n);
+                days
+            }
+            ReportFileStrategy::Files(_) | ReportFileStrategy::Default => {
+                days.truncate(1);
+                days
+            }
+            ReportFileStrategy::Interactive => Self::try_ask_days(days)?,
+        })
+    }
+
     fn strategy(&'a self) -> ReportFileStrategy<'a> {
         match self.last {
             Some(last) => ReportFileStrategy::Last(last),
@@ -108,9 +196,12 @@ impl<'a> ReportFileArgs {
             .filter_map(|e| e.ok())
             .filter_map(|e| {
                 let path = e.path();
-                let is_jsonl = path.extension().and_then(|x| x.to_str()) == Some("jsonl");
 
-                if is_jsonl { Some(path) } else { None }
+                if archive::is_logfile(&path) {
+                    Some(path)
+                } else {
+                    None
+                }
             }))
     }
 
@@ -137,6 +228,24 @@ impl<'a> ReportFileArgs {
             .collect())
     }
 
+    fn try_ask_days(days: Vec<NaiveDate>) -> Result<Vec<NaiveDate>> {
+        let options = days.iter().map(|day| day.to_string()).collect::<Vec<_>>();
+
+        let indices: HashSet<usize> =
+            MultiSelect::new("Bitte wähle einen oder mehrere Tage aus", options)
+                .raw_prompt()?
+                .into_iter()
+                .map(|list| list.index)
+                .collect();
+
+        Ok(days
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| indices.contains(i))
+            .map(|(_, day)| day)
+            .collect())
+    }
+
     fn to_sorted(logfiles: &[PathBuf]) -> Vec<PathBuf> {
         let mut files = logfiles.to_vec();
         Self::sort_logfiles(&mut files);
@@ -144,15 +253,19 @@ impl<'a> ReportFileArgs {
         files
     }
 
-    fn sort_logfiles(logfiles: &mut Vec<PathBuf>) {
+    fn sort_logfiles(logfiles: &mut [PathBuf]) {
         sort_by_filename_date(logfiles, |p| p.to_str().unwrap_or(""));
     }
 }
 
-#[derive(ValueEnum, Debug, Clone)]
+#[derive(ValueEnum, Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "lowercase")]
 pub enum ReportMode {
     Simple,
     Outages,
+    Phases,
+    Slow,
+    Uptime,
     Cleanup,
 }
 
@@ -0,0 +1,32 @@
+use clap::Args;
+
+pub const DEFAULT_SERVICE_NAME: &str = "netcheck";
+pub const DEFAULT_SERVICE_WATCHDOG: u64 = 60;
+
+#[derive(Args, Debug)]
+pub struct InstallServiceArgs {
+    /// Flag: Installs a system service into /etc/systemd/system instead of a user service.
+    #[arg(long)]
+    pub system: bool,
+
+    /// Sets the name of the unit.
+    #[arg(long, default_value = DEFAULT_SERVICE_NAME)]
+    pub name: String,
+
+    /// Sets the seconds after which systemd restarts a monitor that stopped responding. 0
+    /// disables the watchdog.
+    #[arg(long, value_name = "SECONDS", default_value_t = DEFAULT_SERVICE_WATCHDOG)]
+    pub watchdog: u64,
+
+    /// Flag: Prints the unit file instead of writing it.
+    #[arg(long)]
+    pub print: bool,
+
+    /// Flag: Overwrites an existing unit file.
+    #[arg(long)]
+    pub force: bool,
+
+    /// Arguments of the monitor the service runs, e.g. '-- --interval 10 --record sample'.
+    #[arg(last = true, value_name = "MONITOR_ARGS")]
+    pub monitor_args: Vec<String>,
+}
@@ -1 +1,25 @@
+use crate::config::Config;
 
+#[derive(clap::Args, Debug, Clone)]
+pub struct DebounceArgs {
+    /// Sets the number of consecutive failed checks before the connection counts as offline.
+    /// [default: 1]
+    #[arg(long, value_name = "N")]
+    pub debounce_failures: Option<u32>,
+
+    /// Sets the number of consecutive successful checks before the connection counts as restored.
+    /// [default: 1]
+    #[arg(long, value_name = "N")]
+    pub debounce_successes: Option<u32>,
+}
+
+impl DebounceArgs {
+    pub fn merge(&self, config: &mut Config) {
+        if let Some(failures) = self.debounce_failures {
+            config.debounce.failures = failures;
+        }
+        if let Some(successes) = self.debounce_successes {
+            config.debounce.successes = successes;
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use std::{
+    collections::BTreeSet,
+    fs::{File, read_dir, remove_file, rename},
+    io::{self, BufRead, BufReader, BufWriter, Read},
+    path::{Path, PathBuf},
+};
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::sort::{newest_per_prefix, parse_logfile_name};
+
+/// Compression of logfiles once they have been rotated out.
+#[derive(ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    fn extension(&self) -> Option<&'static str> {
+        match self {
+            Compression::None => None,
+            Compression::Gzip => Some("gz"),
+            Compression::Zstd => Some("zst"),
+        }
+    }
+
+    fn of(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("gz") => Compression::Gzip,
+            Some("zst") => Compression::Zstd,
+            _ => Compression::None,
+        }
+    }
+}
+
+/// Whether the path is a logfile, plain or compressed.
+pub fn is_logfile(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+
+    name.ends_with(".jsonl") || name.ends_with(".jsonl.gz") || name.ends_with(".jsonl.zst")
+}
+
+/// The newest logfile of every prefix in the directories of `paths`, which a running monitor may
+/// still write into. The paths are canonical, so they can be compared regardless of how the files
+/// were given.
+pub fn newest_in_dirs(paths: &[PathBuf]) -> Vec<PathBuf> {
+    let dirs = paths
+        .iter()
+        .filter_map(|path| path.canonicalize().ok()?.parent().map(Path::to_path_buf))
+        .collect::<BTreeSet<_>>();
+    let logfiles = dirs
+        .iter()
+        .filter_map(|dir| read_dir(dir).ok())
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| is_logfile(path))
+        .collect::<Vec<_>>();
+
+    newest_per_prefix(&logfiles)
+        .into_iter()
+        .map(Path::to_path_buf)
+        .collect()
+}
+
+/// Whether `path` is one of the logfiles returned by [`newest_in_dirs`].
+pub fn is_newest(path: &Path, newest: &[PathBuf]) -> bool {
+    path.canonicalize().is_ok_and(|path| newest.contains(&path))
+}
+
+/// Opens a logfile for reading and decompresses it on the fly, if needed.
+pub fn open(path: &Path) -> io::Result<Box<dyn BufRead>> {
+    let file = File::open(path)?;
+
+    Ok(match Compression::of(path) {
+        Compression::Gzip => Box::new(BufReader::new(flate2::read::MultiGzDecoder::new(file))),
+        Compression::Zstd => Box::new(BufReader::new(zstd::stream::read::Decoder::new(file)?)),
+        Compression::None => Box::new(BufReader::new(file)),
+    })
+}
+
+/// Plain logfiles in `dir` named `<prefix>_<date>_<index>.jsonl`, except for `current`, which are
+/// due to be compressed. Logfiles of monitors with a longer prefix, like `<prefix>_lab`, are left
+/// alone.
+pub fn rotated(dir: &Path, prefix: &str, current: &Path) -> io::Result<Vec<PathBuf>> {
+    Ok(read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path != current)
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .filter(|name| name.ends_with(".jsonl"))
+                .and_then(parse_logfile_name)
+                .is_some_and(|(name_prefix, ..)| name_prefix == prefix)
+        })
+        .collect())
+}
+
+/// Whether a compressed copy of the logfile exists, so its name must not be used again.
+pub fn is_archived(path: &Path) -> bool {
+    [Compression::Gzip, Compression::Zstd]
+        .iter()
+        .filter_map(|compression| compression.extension())
+        .any(|extension| archive_path(path, extension).exists())
+}
+
+/// Compresses a logfile next to the original, which is removed afterwards. If compressing fails,
+/// the original is kept. The compressed file is written under a temporary name first, so a
+/// compression that was interrupted never leaves a partial archive behind.
+pub fn compress(path: &Path, compression: Compression) -> io::Result<PathBuf> {
+    let Some(extension) = compression.extension() else {
+        return Ok(path.to_path_buf());
+    };
+    let target = archive_path(path, extension);
+    let temporary = archive_path(&target, "tmp");
+
+    if let Err(err) =
+        File::open(path).and_then(|source| write_compressed(source, &temporary, compression))
+    {
+        let _ = remove_file(&temporary);
+        return Err(err);
+    }
+    rename(&temporary, &target)?;
+    remove_file(path)?;
+
+    Ok(target)
+}
+
+fn archive_path(path: &Path, extension: &str) -> PathBuf {
+    let mut archive = path.as_os_str().to_owned();
+    archive.push(".");
+    archive.push(extension);
+
+    PathBuf::from(archive)
+}
+
+/// Replaces the content of a logfile, compressed like the original. The content is written next
+/// to it first, so readers see either the old or the new content.
+pub fn rewrite(path: &Path, content: &[u8]) -> io::Result<()> {
+    let temporary = archive_path(path, "tmp");
+
+    if let Err(err) = write_compressed(content, &temporary, Compression::of(path)) {
+        let _ = remove_file(&temporary);
+        return Err(err);
+    }
+
+    rename(&temporary, path)
+}
+
+fn write_compressed(
+    mut source: impl Read,
+    target: &Path,
+    compression: Compression,
+) -> io::Result<()> {
+    // A temporary file left behind by an interrupted run is overwritten.
+    let writer = BufWriter::new(File::create(target)?);
+
+    let file = match compression {
+        Compression::Gzip => {
+            let mut encoder = flate2::write::GzEncoder::new(writer, flate2::Compression::default());
+            io::copy(&mut source, &mut encoder)?;
+            encoder.finish()?
+        }
+        Compression::Zstd => {
+            let mut encoder = zstd::stream::write::Encoder::new(writer, 0)?;
+            io::copy(&mut source, &mut encoder)?;
+            encoder.finish()?
+        }
+        Compression::None => {
+            let mut writer = writer;
+            io::copy(&mut source, &mut writer)?;
+            writer
+        }
+    };
+
+    file.into_inner()
+        .map_err(|err| err.into_error())?
+        .sync_all()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{create_dir_all, remove_dir_all, write};
+
+    use super::*;
+
+    #[test]
+    fn rotated_leaves_monitors_with_a_longer_prefix_alone() {
+        let dir = std::env::temp_dir().join(format!("netcheck-rotated-{}", std::process::id()));
+        let _ = remove_dir_all(&dir);
+        create_dir_all(&dir).unwrap();
+        for name in [
+            "netcheck_2026-10-17_0.jsonl",
+            "netcheck_2026-10-18_0.jsonl",
+            "netcheck_lab_2026-10-18_0.jsonl",
+            "netcheck_2026-10-16_0.jsonl.gz",
+        ] {
+            write(dir.join(name), "").unwrap();
+        }
+
+        let rotated = rotated(&dir, "netcheck", &dir.join("netcheck_2026-10-18_0.jsonl")).unwrap();
+        assert_eq!(rotated, vec![dir.join("netcheck_2026-10-17_0.jsonl")]);
+
+        let _ = remove_dir_all(&dir);
+    }
+}
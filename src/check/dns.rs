@@ -0,0 +1,217 @@
+use std::{
+    net::IpAddr,
+    time::{Duration, Instant},
+};
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpStream, UdpSocket, lookup_host},
+    time::{self, timeout as with_timeout},
+};
+
+use crate::{
+    check::{ProbeOutcome, classify_io_error},
+    model::{CheckError, LatencyPhases, ProbeResult},
+};
+
+const DNS_PORT: u16 = 53;
+const RECORD_A: u16 = 1;
+const RECORD_AAAA: u16 = 28;
+const CLASS_IN: u16 = 1;
+const RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Resolves the domain with the system resolver or, if given, by querying the resolver directly.
+/// A lookup without any address counts as DNS failure.
+pub async fn probe(domain: &str, resolver: Option<IpAddr>, timeout: Duration) -> ProbeOutcome {
+    let lookup = async {
+        match resolver {
+            Some(resolver) => query(domain, resolver).await,
+            None => lookup_system(domain).await,
+        }
+    };
+
+    let start = Instant::now();
+    let lookup = with_timeout(timeout, lookup).await;
+    let phases = LatencyPhases {
+        dns: Some(start.elapsed()),
+        ..Default::default()
+    };
+
+    let (addresses, error) = match lookup {
+        Ok(Ok(addresses)) if addresses.is_empty() => (addresses, Some(CheckError::DnsFailure)),
+        Ok(Ok(addresses)) => (addresses, None),
+        Ok(Err(error)) => (vec![], Some(error)),
+        Err(_) => (vec![], Some(CheckError::Timeout)),
+    };
+
+    ProbeOutcome::new(ProbeResult::Dns { addresses }, None, error).with_phases(phases)
+}
+
+async fn lookup_system(domain: &str) -> Result<Vec<IpAddr>, CheckError> {
+    Ok(lookup_host((domain, 0))
+        .await
+        .map_err(|_| CheckError::DnsFailure)?
+        .map(|addr| addr.ip())
+        .collect())
+}
+
+async fn query(domain: &str, resolver: IpAddr) -> Result<Vec<IpAddr>, CheckError> {
+    let id = query_id();
+    let request = build_query(id, domain)?;
+
+    let response = match query_udp(&request, id, resolver).await? {
+        response if is_truncated(&response) => query_tcp(&request, resolver).await?,
+        response => response,
+    };
+
+    parse_response(id, &response).ok_or(CheckError::Other("Malformed DNS response".into()))?
+}
+
+/// Sends the query over UDP, again every [`RETRY_INTERVAL`] since datagrams may get lost, until an
+/// answer to it arrives. Answers with another id are ignored. The timeout of the probe ends the
+/// retries.
+async fn query_udp(request: &[u8], id: u16, resolver: IpAddr) -> Result<Vec<u8>, CheckError> {
+    let local = if resolver.is_ipv4() {
+        "0.0.0.0:0"
+    } else {
+        "[::]:0"
+    };
+
+    let socket = UdpSocket::bind(local)
+        .await
+        .map_err(|e| classify_io_error(&e))?;
+    socket
+        .connect((resolver, DNS_PORT))
+        .await
+        .map_err(|e| classify_io_error(&e))?;
+
+    let mut buffer = [0u8; 512];
+    loop {
+        socket
+            .send(request)
+            .await
+            .map_err(|e| classify_io_error(&e))?;
+
+        let retry = time::Instant::now() + RETRY_INTERVAL;
+        while let Ok(received) = time::timeout_at(retry, socket.recv(&mut buffer)).await {
+            let len = received.map_err(|e| classify_io_error(&e))?;
+
+            if read_u16(&buffer[..len], 0) == Some(id) {
+                return Ok(buffer[..len].to_vec());
+            }
+        }
+    }
+}
+
+/// Sends the query over TCP, for answers that don't fit into a UDP datagram.
+async fn query_tcp(request: &[u8], resolver: IpAddr) -> Result<Vec<u8>, CheckError> {
+    let mut stream = TcpStream::connect((resolver, DNS_PORT))
+        .await
+        .map_err(|e| classify_io_error(&e))?;
+
+    let mut message = (request.len() as u16).to_be_bytes().to_vec();
+    message.extend_from_slice(request);
+    stream
+        .write_all(&message)
+        .await
+        .map_err(|e| classify_io_error(&e))?;
+
+    let len = stream.read_u16().await.map_err(|e| classify_io_error(&e))?;
+    let mut response = vec![0; len as usize];
+    stream
+        .read_exact(&mut response)
+        .await
+        .map_err(|e| classify_io_error(&e))?;
+
+    Ok(response)
+}
+
+/// Random id, so answers to other queries or spoofed ones can't be taken for the answer.
+fn query_id() -> u16 {
+    getrandom::u32().map(|id| id as u16).unwrap_or_default()
+}
+
+/// Whether the resolver set the TC flag, since the answer didn't fit into the datagram.
+fn is_truncated(response: &[u8]) -> bool {
+    read_u16(response, 2).is_some_and(|flags| flags & 0x0200 != 0)
+}
+
+/// Builds a recursive query for the A records of the domain.
+fn build_query(id: u16, domain: &str) -> Result<Vec<u8>, CheckError> {
+    let mut request = Vec::with_capacity(512);
+    request.extend_from_slice(&id.to_be_bytes());
+    request.extend_from_slice(&[0x01, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+
+    for label in domain.trim_end_matches('.').split('.') {
+        if label.is_empty() || label.len() > 63 {
+            return Err(CheckError::InvalidRequest);
+        }
+
+        request.push(label.len() as u8);
+        request.extend_from_slice(label.as_bytes());
+    }
+    request.push(0);
+    request.extend_from_slice(&RECORD_A.to_be_bytes());
+    request.extend_from_slice(&CLASS_IN.to_be_bytes());
+
+    Ok(request)
+}
+
+/// Extracts all addresses from the answer section. Returns `None` if the response is malformed
+/// and a DNS failure if the resolver answered with an error code.
+fn parse_response(id: u16, data: &[u8]) -> Option<Result<Vec<IpAddr>, CheckError>> {
+    if read_u16(data, 0)? != id {
+        return None;
+    }
+
+    let rcode = read_u16(data, 2)? & 0x000f;
+    if rcode != 0 {
+        return Some(Err(CheckError::DnsFailure));
+    }
+
+    let questions = read_u16(data, 4)?;
+    let answers = read_u16(data, 6)?;
+    let mut pos = 12;
+
+    for _ in 0..questions {
+        pos = skip_name(data, pos)? + 4;
+    }
+
+    let mut addresses = vec![];
+    for _ in 0..answers {
+        pos = skip_name(data, pos)?;
+        let record_type = read_u16(data, pos)?;
+        let len = read_u16(data, pos + 8)? as usize;
+        pos += 10;
+
+        let rdata = data.get(pos..pos + len)?;
+        match (record_type, len) {
+            (RECORD_A, 4) => addresses.push(IpAddr::from(<[u8; 4]>::try_from(rdata).ok()?)),
+            (RECORD_AAAA, 16) => addresses.push(IpAddr::from(<[u8; 16]>::try_from(rdata).ok()?)),
+            _ => {}
+        }
+        pos += len;
+    }
+
+    Some(Ok(addresses))
+}
+
+fn skip_name(data: &[u8], mut pos: usize) -> Option<usize> {
+    loop {
+        let len = *data.get(pos)? as usize;
+
+        if len == 0 {
+            return Some(pos + 1);
+        }
+        if len & 0xc0 == 0xc0 {
+            return Some(pos + 2);
+        }
+
+        pos += len + 1;
+    }
+}
+
+fn read_u16(data: &[u8], pos: usize) -> Option<u16> {
+    data.get(pos..pos + 2)
+        .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+}
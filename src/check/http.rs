@@ -0,0 +1,248 @@
+use std::{
+    error::Error,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex, OnceLock},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use reqwest::{
+    Client,
+    dns::{Addrs, Name, Resolve, Resolving},
+    header::CONTENT_TYPE,
+};
+use tokio::net::lookup_host;
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::{
+    check::ProbeOutcome,
+    model::{CheckError, LatencyPhases, ProbeResult},
+};
+
+static CLIENT: OnceLock<Client> = OnceLock::new();
+
+tokio::task_local! {
+    /// Timings of the connection opened for the probe that runs in this task.
+    static TIMINGS: Arc<Mutex<Timings>>;
+}
+
+/// When the name was resolved and the connection was ready, measured from the start of the
+/// connection. Only the first connection of a request is timed, redirects open further ones.
+#[derive(Default)]
+struct Timings {
+    dns: Option<Duration>,
+    connected: Option<Duration>,
+}
+
+/// Sends a GET request, which follows redirects and uses the proxy of the `*_PROXY` environment
+/// variables like any other client. The phases are timed on the connection of the request itself:
+/// resolving the name, connecting, which includes the TLS handshake for HTTPS, and waiting for the
+/// response. Since the client doesn't expose the handshake, it isn't timed on its own.
+pub async fn probe(url: &str, expected_status: Option<u16>, timeout: Duration) -> ProbeOutcome {
+    let timings = Arc::new(Mutex::new(Timings::default()));
+    let (status, error, latency) = TIMINGS
+        .scope(Arc::clone(&timings), get(url, expected_status, timeout))
+        .await;
+
+    let mut phases = LatencyPhases::default();
+    if let Ok(timings) = timings.lock() {
+        phases.dns = timings.dns;
+        phases.connect = timings
+            .connected
+            .map(|connected| connected.saturating_sub(timings.dns.unwrap_or_default()));
+        phases.first_byte = timings
+            .connected
+            .filter(|_| status.is_some())
+            .map(|connected| latency.saturating_sub(connected));
+    }
+
+    ProbeOutcome::new(ProbeResult::Http, status, error)
+        .with_latency(latency)
+        .with_phases(phases)
+}
+
+/// Posts the JSON body and returns the status code. Only a 2xx status code after following all
+/// redirects counts as delivered.
+pub async fn post_json(url: &str, body: &[u8], timeout: Duration) -> Result<u16, CheckError> {
+    let response = client()?
+        .post(url)
+        .header(CONTENT_TYPE, "application/json")
+        .body(body.to_vec())
+        .timeout(timeout)
+        .send()
+        .await
+        .map_err(classify_reqwest_error)?;
+    let status = response.status().as_u16();
+
+    match accepts_status(status, None) {
+        true => Ok(status),
+        false => Err(CheckError::HttpStatus(status)),
+    }
+}
+
+async fn get(
+    url: &str,
+    expected_status: Option<u16>,
+    timeout: Duration,
+) -> (Option<u16>, Option<CheckError>, Duration) {
+    let client = match client() {
+        Ok(client) => client,
+        Err(error) => return (None, Some(error), Duration::ZERO),
+    };
+
+    let start = Instant::now();
+    let response = client.get(url).timeout(timeout).send().await;
+    let latency = start.elapsed();
+
+    match response {
+        Ok(res) => {
+            let status = res.status().as_u16();
+            let error = if accepts_status(status, expected_status) {
+                None
+            } else {
+                Some(CheckError::HttpStatus(status))
+            };
+
+            (Some(status), error, latency)
+        }
+        Err(err) => (None, Some(classify_reqwest_error(err)), latency),
+    }
+}
+
+/// Checks the status code against the expected status. Without an expected status, every 2xx
+/// status code counts as success.
+fn accepts_status(status: u16, expected_status: Option<u16>) -> bool {
+    match expected_status {
+        Some(expected) => expected == status,
+        None => (200..300).contains(&status),
+    }
+}
+
+/// Shared client without idle connections, so every check opens a fresh connection, whose phases
+/// are timed. A client that fails to build is not kept, the next check tries again.
+fn client() -> Result<&'static Client, CheckError> {
+    if let Some(client) = CLIENT.get() {
+        return Ok(client);
+    }
+
+    let client = Client::builder()
+        .user_agent(concat!("netcheck/", env!("CARGO_PKG_VERSION")))
+        .pool_max_idle_per_host(0)
+        .dns_resolver(TimedResolver)
+        .connector_layer(TimedConnectLayer)
+        .build()
+        .map_err(classify_reqwest_error)?;
+
+    Ok(CLIENT.get_or_init(|| client))
+}
+
+fn classify_reqwest_error(err: reqwest::Error) -> CheckError {
+    if err.is_timeout() {
+        return CheckError::Timeout;
+    }
+
+    if err.is_builder()
+        || err.to_string().to_lowercase().contains("tls")
+        || err.to_string().to_lowercase().contains("certificate")
+    {
+        return CheckError::TlsError;
+    }
+
+    if err.is_connect() {
+        if let Some(source) = err.source() {
+            let msg = source.to_string().to_lowercase();
+
+            if msg.contains("dns") || msg.contains("resolve") {
+                return CheckError::DnsFailure;
+            }
+
+            if msg.contains("refused") {
+                return CheckError::ConnectionRefused;
+            }
+
+            if msg.contains("ssl") || msg.contains("tls") || msg.contains("certificate") {
+                return CheckError::TlsError;
+            }
+        }
+
+        return CheckError::ConnectionRefused;
+    }
+
+    if err.is_status()
+        && let Some(status) = err.status()
+    {
+        return CheckError::HttpStatus(status.as_u16());
+    }
+
+    if err.is_request() {
+        return CheckError::InvalidRequest;
+    }
+
+    CheckError::Other(err.to_string())
+}
+
+/// Resolves names like the system resolver and records the DNS phase of the running probe.
+struct TimedResolver;
+
+impl Resolve for TimedResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let timings = TIMINGS.try_with(Arc::clone).ok();
+
+        Box::pin(async move {
+            let start = Instant::now();
+            let addresses = lookup_host((name.as_str(), 0)).await?.collect::<Vec<_>>();
+            if let Some(Ok(mut timings)) = timings.as_ref().map(|timings| timings.lock()) {
+                timings.dns.get_or_insert(start.elapsed());
+            }
+
+            Ok(Box::new(addresses.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Records when the connection of the running probe is ready, including name resolution, the
+/// TLS handshake and a proxy tunnel.
+#[derive(Clone)]
+struct TimedConnectLayer;
+
+impl<S> Layer<S> for TimedConnectLayer {
+    type Service = TimedConnect<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TimedConnect(inner)
+    }
+}
+
+#[derive(Clone)]
+struct TimedConnect<S>(S);
+
+impl<S, R> Service<R> for TimedConnect<S>
+where
+    S: Service<R>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<S::Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.0.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: R) -> Self::Future {
+        let timings = TIMINGS.try_with(Arc::clone).ok();
+        let connecting = self.0.call(request);
+
+        Box::pin(async move {
+            let start = Instant::now();
+            let connection = connecting.await?;
+            if let Some(Ok(mut timings)) = timings.as_ref().map(|timings| timings.lock()) {
+                timings.connected.get_or_insert(start.elapsed());
+            }
+
+            Ok(connection)
+        })
+    }
+}
@@ -0,0 +1,186 @@
+use std::{
+    borrow::Borrow,
+    io,
+    time::{Duration, Instant},
+};
+
+use futures::future::join_all;
+
+use crate::{
+    config::DegradedConfig,
+    model::{
+        CheckError, CheckTarget, Connectivity, InternetCheckCycle, InternetCheckResult, Latency,
+        LatencyPhases, LatencySpeed, Probe, ProbeResult, TargetResult,
+    },
+};
+
+mod dns;
+mod http;
+mod tcp;
+
+pub use http::post_json;
+
+pub fn default_targets() -> Vec<CheckTarget> {
+    vec![
+        CheckTarget::http("Google", "https://google.com/generate_204"),
+        CheckTarget::http("Example", "https://example.com"),
+        CheckTarget::http("IP", "https://1.1.1.1"),
+    ]
+}
+
+pub async fn check_connection(
+    targets: &[CheckTarget],
+    degraded: &DegradedConfig,
+    timeout: Duration,
+    latency_threshold: u64,
+    check_cycle: InternetCheckCycle,
+) -> InternetCheckResult {
+    let results = join_all(
+        targets
+            .iter()
+            .map(|target| check_target(target, timeout, latency_threshold)),
+    )
+    .await;
+
+    let speed = LatencySpeed::new(&results.iter().collect::<Vec<_>>(), latency_threshold);
+    let avg = avg_durations(
+        results
+            .iter()
+            .filter(|r| r.success())
+            .map(|r| r.latency_duration()),
+    );
+    let connectivity = rate_connectivity(&results, &speed, degraded);
+
+    InternetCheckResult::new(
+        connectivity,
+        speed,
+        latency_threshold,
+        results,
+        avg,
+        check_cycle,
+    )
+}
+
+/// The connection is offline if no target is reachable and degraded if one of the degraded rules
+/// applies to the reachable targets.
+fn rate_connectivity(
+    results: &[TargetResult],
+    speed: &LatencySpeed,
+    degraded: &DegradedConfig,
+) -> Connectivity {
+    let succeeded = results.iter().filter(|r| r.success()).collect::<Vec<_>>();
+    if succeeded.is_empty() {
+        return Connectivity::Offline;
+    }
+
+    let failed_share = (results.len() - succeeded.len()) as f64 / results.len() as f64;
+    let too_slow = match degraded.latency {
+        Some(latency) => {
+            // Like the speed of a check, only a latency above the threshold counts as too slow.
+            avg_durations(succeeded.iter().map(|r| r.latency_duration())).as_millis()
+                > latency as u128
+        }
+        None => matches!(speed, LatencySpeed::Slow),
+    };
+
+    if (succeeded.len() < results.len() && failed_share >= degraded.failed_share) || too_slow {
+        Connectivity::Degraded
+    } else {
+        Connectivity::Online
+    }
+}
+
+struct ProbeOutcome {
+    probe: ProbeResult,
+    status_code: Option<u16>,
+    error: Option<CheckError>,
+    latency: Option<Duration>,
+    phases: LatencyPhases,
+}
+
+impl ProbeOutcome {
+    fn new(probe: ProbeResult, status_code: Option<u16>, error: Option<CheckError>) -> Self {
+        Self {
+            probe,
+            status_code,
+            error,
+            latency: None,
+            phases: LatencyPhases::default(),
+        }
+    }
+
+    /// Latency measured by the probe itself, instead of around the whole probe.
+    fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = Some(latency);
+
+        self
+    }
+
+    fn with_phases(mut self, phases: LatencyPhases) -> Self {
+        self.phases = phases;
+
+        self
+    }
+}
+
+async fn check_target(
+    target: &CheckTarget,
+    timeout: Duration,
+    latency_threshold: u64,
+) -> TargetResult {
+    let timeout = target.timeout().unwrap_or(timeout);
+    let latency_threshold = target.latency_threshold().unwrap_or(latency_threshold);
+
+    let start = Instant::now();
+    let outcome = match target.probe() {
+        Probe::Http {
+            url,
+            expected_status,
+        } => http::probe(url, *expected_status, timeout).await,
+        Probe::Tcp { address } => tcp::probe(address, timeout).await,
+        Probe::Dns { domain, resolver } => dns::probe(domain, *resolver, timeout).await,
+    };
+    let latency = outcome.latency.unwrap_or(start.elapsed());
+
+    TargetResult::new(
+        target.name().to_string(),
+        outcome.probe,
+        outcome.error.is_none(),
+        Latency::from_duration(latency, latency_threshold).with_phases(outcome.phases),
+        outcome.status_code,
+        outcome.error,
+    )
+}
+
+fn avg_durations<I>(durations: I) -> Duration
+where
+    I: IntoIterator,
+    I::Item: Borrow<Duration>,
+{
+    let mut total = Duration::ZERO;
+    let mut count = 0u32;
+
+    for d in durations {
+        total += *d.borrow();
+        count += 1;
+    }
+
+    if count == 0 {
+        return Duration::ZERO;
+    }
+
+    total / count
+}
+
+fn classify_io_error(err: &io::Error) -> CheckError {
+    match err.kind() {
+        io::ErrorKind::TimedOut => CheckError::Timeout,
+        io::ErrorKind::ConnectionRefused | io::ErrorKind::ConnectionReset => {
+            CheckError::ConnectionRefused
+        }
+        io::ErrorKind::HostUnreachable | io::ErrorKind::NetworkUnreachable => {
+            CheckError::Unreachable
+        }
+        _ => CheckError::Other(err.to_string()),
+    }
+}
@@ -0,0 +1,70 @@
+use std::{
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+use tokio::{
+    net::{TcpStream, ToSocketAddrs, lookup_host},
+    time::timeout as with_timeout,
+};
+
+use crate::{
+    check::{ProbeOutcome, classify_io_error},
+    model::{CheckError, LatencyPhases, ProbeResult},
+};
+
+/// Resolves the address and connects to it. The name resolution is done upfront, so that a
+/// broken resolver is reported as DNS failure and not as unreachable host.
+pub async fn probe(address: &str, timeout: Duration) -> ProbeOutcome {
+    let mut phases = LatencyPhases::default();
+
+    let (peer, error) = match with_timeout(timeout, open(address, &mut phases)).await {
+        Ok(Ok(stream)) => (stream.peer_addr().ok(), None),
+        Ok(Err(error)) => (None, Some(error)),
+        Err(_) => (None, Some(CheckError::Timeout)),
+    };
+
+    ProbeOutcome::new(ProbeResult::Tcp { peer }, None, error).with_phases(phases)
+}
+
+/// Resolves the address and records the DNS phase.
+async fn resolve<A: ToSocketAddrs>(
+    address: A,
+    phases: &mut LatencyPhases,
+) -> Result<Vec<SocketAddr>, CheckError> {
+    let start = Instant::now();
+    let addresses = lookup_host(address)
+        .await
+        .map_err(|_| CheckError::DnsFailure)?
+        .collect::<Vec<SocketAddr>>();
+    phases.dns = Some(start.elapsed());
+
+    Ok(addresses)
+}
+
+/// Connects to the first reachable address and records the connect phase.
+async fn connect(
+    addresses: &[SocketAddr],
+    phases: &mut LatencyPhases,
+) -> Result<TcpStream, CheckError> {
+    let start = Instant::now();
+    let mut error = CheckError::DnsFailure;
+
+    for addr in addresses {
+        match TcpStream::connect(addr).await {
+            Ok(stream) => {
+                phases.connect = Some(start.elapsed());
+                return Ok(stream);
+            }
+            Err(err) => error = classify_io_error(&err),
+        }
+    }
+
+    Err(error)
+}
+
+async fn open(address: &str, phases: &mut LatencyPhases) -> Result<TcpStream, CheckError> {
+    let addresses = resolve(address, phases).await?;
+
+    connect(&addresses, phases).await
+}
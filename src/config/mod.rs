@@ -0,0 +1,413 @@
+use std::{
+    collections::HashSet,
+    fs::read_to_string,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app::{
+        config::{ConfigAction, ConfigArgs},
+        monitor::{DEFAULT_RECORD_MODE, RecordMode},
+        report::{DEFAULT_REPORT_MODE, ReportMode},
+    },
+    archive::Compression,
+    check::default_targets,
+    log::{
+        ConsoleFormat, DEFAULT_COMPRESSION, DEFAULT_CONSOLE_FORMAT, DEFAULT_FILE_PREFIX,
+        DEFAULT_LOG_MODE, DEFAULT_MAX_SIZE, LogMode,
+    },
+    model::{CheckTarget, DEFAULT_LATENCY_THRESHOLD, OutageLogPrecision},
+    monitor::{
+        DEFAULT_MONITOR_EXCLUDE_STOPPED, DEFAULT_MONITOR_HEARTBEAT, DEFAULT_MONITOR_INTERVAL,
+        DEFAULT_MONITOR_TIMEOUT,
+    },
+    notify::Transition,
+    project::Project,
+};
+
+mod show;
+
+pub const DEFAULT_CONFIG_FILE: &str = "netcheck.toml";
+pub const DEFAULT_DEGRADED_FAILED_SHARE: f64 = 0.5;
+pub const DEFAULT_DEBOUNCE_FAILURES: u32 = 1;
+pub const DEFAULT_DEBOUNCE_SUCCESSES: u32 = 1;
+pub const DEFAULT_HOOK_TIMEOUT: u64 = 30;
+pub const DEFAULT_WEBHOOK_TIMEOUT: u64 = 10;
+
+pub fn run(args: ConfigArgs, config: Config) -> Result<()> {
+    match args.action {
+        ConfigAction::Show => show::handle(config),
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    #[serde(skip)]
+    source: Option<PathBuf>,
+
+    pub monitor: MonitorConfig,
+    pub degraded: DegradedConfig,
+    pub debounce: DebounceConfig,
+    pub log: LogConfig,
+    pub report: ReportConfig,
+    pub targets: Vec<CheckTarget>,
+    pub hooks: Vec<HookConfig>,
+    pub webhooks: Vec<WebhookConfig>,
+    pub metrics: MetricsConfig,
+    pub status: StatusConfig,
+    pub retention: RetentionConfig,
+}
+
+impl Config {
+    /// Loads the config file from the given path or, if no path is given, from the project
+    /// config directory. Only an explicitly given config file is required to exist.
+    pub fn load(path: Option<&Path>, project: &Project) -> Result<Self> {
+        let (path, required) = match path {
+            Some(path) => (path.to_path_buf(), true),
+            None => (project.config_file(), false),
+        };
+
+        if !required && !path.exists() {
+            return Ok(Config::default());
+        }
+
+        let content = read_to_string(&path)
+            .with_context(|| format!("Failed to read config file '{}'", path.display()))?;
+        let mut config: Config = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse config file '{}'", path.display()))?;
+        // A bare 'report' must never remove files.
+        if matches!(config.report.mode, ReportMode::Cleanup) {
+            return Err(anyhow::anyhow!(
+                "Invalid config file '{}': 'cleanup' can't be the default report mode, use --mode cleanup",
+                path.display()
+            ));
+        }
+        config
+            .check_targets()
+            .with_context(|| format!("Invalid config file '{}'", path.display()))?;
+        config.source = Some(path);
+
+        Ok(config)
+    }
+
+    /// Checks that there is any target and that every name is used once, since the metrics and
+    /// the dashboard tell the targets apart by name.
+    pub fn check_targets(&self) -> Result<()> {
+        if self.targets.is_empty() {
+            return Err(anyhow::anyhow!("'targets' can't be empty"));
+        }
+
+        let mut names = HashSet::new();
+        match self
+            .targets
+            .iter()
+            .find(|target| !names.insert(target.name()))
+        {
+            Some(target) => Err(anyhow::anyhow!(
+                "The target name '{}' is used more than once",
+                target.name()
+            )),
+            None => Ok(()),
+        }
+    }
+
+    pub fn source(&self) -> Option<&Path> {
+        self.source.as_deref()
+    }
+
+    pub fn log_dir<'a>(&'a self, project: &'a Project) -> &'a Path {
+        match self.log.dir.as_deref() {
+            Some(path) => path,
+            None => project.log_dir(),
+        }
+    }
+
+    /// SQLite database of the log entries, if any. A relative path is resolved against the log
+    /// directory.
+    pub fn database(&self, project: &Project) -> Option<PathBuf> {
+        self.log
+            .database
+            .as_deref()
+            .map(|database| self.log_dir(project).join(database))
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            source: None,
+            monitor: MonitorConfig::default(),
+            degraded: DegradedConfig::default(),
+            debounce: DebounceConfig::default(),
+            log: LogConfig::default(),
+            report: ReportConfig::default(),
+            targets: default_targets(),
+            hooks: vec![],
+            webhooks: vec![],
+            metrics: MetricsConfig::default(),
+            status: StatusConfig::default(),
+            retention: RetentionConfig::default(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct MonitorConfig {
+    pub interval: u64,
+    pub timeout: u64,
+    pub exclude_stopped: bool,
+    pub latency_threshold: u64,
+
+    /// Which checks are logged: only the transitions, every check or every check as a sample with
+    /// the failed targets only.
+    pub record: RecordMode,
+
+    /// Seconds between the heartbeats, which tell the reports that the monitor was running. 0
+    /// disables them.
+    pub heartbeat: u64,
+
+    /// Shows a live dashboard instead of the log lines on stdout.
+    pub tui: bool,
+
+    /// Interval in seconds while the connection is offline or an outage is about to be confirmed.
+    /// Without a value, the regular interval is used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offline_interval: Option<u64>,
+
+    /// Interval in seconds while the connection is degraded. Without a value, the regular interval
+    /// is used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub degraded_interval: Option<u64>,
+
+    /// Enables backoff while the connection is stable: the interval doubles after every check up
+    /// to this many seconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_interval: Option<u64>,
+}
+
+impl Default for MonitorConfig {
+    fn default() -> Self {
+        Self {
+            interval: DEFAULT_MONITOR_INTERVAL,
+            timeout: DEFAULT_MONITOR_TIMEOUT,
+            exclude_stopped: DEFAULT_MONITOR_EXCLUDE_STOPPED,
+            latency_threshold: DEFAULT_LATENCY_THRESHOLD,
+            record: DEFAULT_RECORD_MODE,
+            heartbeat: DEFAULT_MONITOR_HEARTBEAT,
+            tui: false,
+            offline_interval: None,
+            degraded_interval: None,
+            max_interval: None,
+        }
+    }
+}
+
+/// Rules that rate a connection as degraded, as long as at least one target is reachable.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct DegradedConfig {
+    /// Share of failed targets (0.0 - 1.0) from which on the connection is degraded.
+    pub failed_share: f64,
+
+    /// Average latency of the reachable targets in milliseconds above which the connection is
+    /// degraded. Without a value, the connection is degraded whenever the check is rated slow.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency: Option<u64>,
+}
+
+impl Default for DegradedConfig {
+    fn default() -> Self {
+        Self {
+            failed_share: DEFAULT_DEGRADED_FAILED_SHARE,
+            latency: None,
+        }
+    }
+}
+
+/// Rules that keep short drops from flipping the connection between online and offline. The
+/// monitor and the reports apply the same rules.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct DebounceConfig {
+    /// Consecutive failed checks before the connection counts as offline.
+    pub failures: u32,
+
+    /// Consecutive successful checks before the connection counts as restored.
+    pub successes: u32,
+}
+
+impl Default for DebounceConfig {
+    fn default() -> Self {
+        Self {
+            failures: DEFAULT_DEBOUNCE_FAILURES,
+            successes: DEFAULT_DEBOUNCE_SUCCESSES,
+        }
+    }
+}
+
+/// Command that runs on connectivity transitions.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct HookConfig {
+    pub command: String,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+
+    /// Transitions that trigger the hook. Without any, every transition does.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub on: Vec<Transition>,
+
+    /// Seconds after which the hook is killed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+}
+
+/// HTTP endpoint that transitions are posted to as JSON.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookConfig {
+    pub url: String,
+
+    /// Transitions that are posted. Without any, every transition is.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub on: Vec<Transition>,
+
+    /// Seconds after which a request is given up and retried later.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+}
+
+/// Limits for the logfiles in the log directory, applied by the running monitor and by `prune`.
+/// The newest logfile is always kept.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetentionConfig {
+    /// Days the logfiles are kept besides the current one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_age: Option<u64>,
+
+    /// Bytes all logfiles may take up together.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_size: Option<u64>,
+
+    /// Number of logfiles that are kept.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_files: Option<usize>,
+
+    /// Writes a summary of every day before its logfiles are removed.
+    pub summarize: bool,
+}
+
+impl RetentionConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.max_age.is_some() || self.max_size.is_some() || self.max_files.is_some()
+    }
+}
+
+/// Prometheus endpoint of the running monitor.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct MetricsConfig {
+    /// Address the `/metrics` endpoint listens on. Without a value, no endpoint is served.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub listen: Option<SocketAddr>,
+}
+
+/// JSON status endpoint of the running monitor, served at `/status`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct StatusConfig {
+    /// TCP address the endpoint listens on.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub listen: Option<SocketAddr>,
+
+    /// Unix socket the endpoint listens on.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub socket: Option<PathBuf>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+    pub filename: String,
+    pub size: u64,
+    pub mode: LogMode,
+
+    /// Format of the lines printed to the terminal. Logfiles are always written as JSONL.
+    pub console: ConsoleFormat,
+
+    /// Compression of logfiles once they have been rotated out.
+    pub compress: Compression,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dir: Option<PathBuf>,
+
+    /// SQLite database the entries are stored in as well. Reports and `find` read from it
+    /// instead of the logfiles.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub database: Option<PathBuf>,
+
+    /// Socket of the journal or syslog for the modes 'journald' and 'syslog', instead of
+    /// '/run/systemd/journal/socket' and '/dev/log'.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub socket: Option<PathBuf>,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            filename: DEFAULT_FILE_PREFIX.into(),
+            size: DEFAULT_MAX_SIZE,
+            mode: DEFAULT_LOG_MODE,
+            console: DEFAULT_CONSOLE_FORMAT,
+            compress: DEFAULT_COMPRESSION,
+            dir: None,
+            database: None,
+            socket: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReportConfig {
+    pub mode: ReportMode,
+    pub exact: bool,
+
+    /// Latency threshold in milliseconds to re-evaluate the logged checks with. Without a value,
+    /// the speed rated at the time of the check is used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_threshold: Option<u64>,
+
+    /// Seconds without any log entry from which on the monitor counts as not running. Without a
+    /// value, three missed heartbeats count, if the monitor wrote heartbeats.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gap_threshold: Option<u64>,
+}
+
+impl ReportConfig {
+    pub fn log_precision(&self) -> Option<OutageLogPrecision> {
+        if self.exact {
+            Some(OutageLogPrecision::Exact)
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for ReportConfig {
+    fn default() -> Self {
+        Self {
+            mode: DEFAULT_REPORT_MODE,
+            exact: false,
+            latency_threshold: None,
+            gap_threshold: None,
+        }
+    }
+}
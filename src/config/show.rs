@@ -0,0 +1,14 @@
+use anyhow::Result;
+
+use crate::config::Config;
+
+pub fn handle(config: Config) -> Result<()> {
+    match config.source() {
+        Some(path) => println!("# Loaded from: {}", path.display()),
+        None => println!("# No config file found, using defaults"),
+    }
+    println!();
+    print!("{}", toml::to_string_pretty(&config)?);
+
+    Ok(())
+}
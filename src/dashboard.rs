@@ -0,0 +1,175 @@
+use std::{
+    io::{Stdout, stdout},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::Result;
+use chrono::{Local, Utc};
+use ratatui::{
+    Frame, Terminal,
+    backend::CrosstermBackend,
+    crossterm::{
+        cursor::{Hide, Show},
+        execute,
+        terminal::{Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
+    },
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, List, ListItem, Paragraph, Sparkline},
+};
+use tokio::task::JoinHandle;
+
+use crate::{
+    model::Connectivity,
+    status::{MonitorStatus, StatusData, TargetStats},
+    time::Humanize,
+};
+
+const REFRESH_INTERVAL: Duration = Duration::from_millis(500);
+const TARGET_HEIGHT: u16 = 3;
+const TARGET_INFO_WIDTH: u16 = 44;
+
+/// Live view of the monitor status on the alternate screen. The terminal is restored when the
+/// dashboard is dropped.
+pub struct Dashboard {
+    task: JoinHandle<()>,
+}
+
+impl Dashboard {
+    pub fn start(status: Arc<MonitorStatus>) -> Result<Self> {
+        let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+        execute!(stdout(), EnterAlternateScreen, Hide, Clear(ClearType::All))?;
+
+        let task = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(REFRESH_INTERVAL);
+
+            loop {
+                ticker.tick().await;
+                let _ = draw(&mut terminal, &status);
+            }
+        });
+
+        Ok(Self { task })
+    }
+}
+
+impl Drop for Dashboard {
+    fn drop(&mut self) {
+        self.task.abort();
+        let _ = execute!(stdout(), Show, LeaveAlternateScreen);
+    }
+}
+
+fn draw(terminal: &mut Terminal<CrosstermBackend<Stdout>>, status: &MonitorStatus) -> Result<()> {
+    let data = status.lock()?;
+    terminal.autoresize()?;
+    terminal.draw(|frame| render(frame, &data))?;
+
+    Ok(())
+}
+
+fn render(frame: &mut Frame, data: &StatusData) {
+    let targets_height = TARGET_HEIGHT * data.targets.len().max(1) as u16;
+    let [header, targets, outages] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Length(targets_height),
+        Constraint::Min(3),
+    ])
+    .areas(frame.area());
+
+    render_header(frame, header, data);
+
+    let rows = Layout::vertical(vec![Constraint::Length(TARGET_HEIGHT); data.targets.len()])
+        .split(targets);
+    data.targets
+        .iter()
+        .zip(rows.iter())
+        .for_each(|((name, stats), area)| render_target(frame, *area, name, stats));
+
+    render_outages(frame, outages, data);
+}
+
+fn render_header(frame: &mut Frame, area: Rect, data: &StatusData) {
+    let (label, color) = match data.connectivity {
+        Some(Connectivity::Online) => ("ONLINE", Color::Green),
+        Some(Connectivity::Degraded) => ("DEGRADED", Color::Yellow),
+        Some(Connectivity::Offline) => ("OFFLINE", Color::Red),
+        None => ("WAITING", Color::Gray),
+    };
+
+    let mut spans = vec![Span::styled(
+        format!(" {label} "),
+        Style::new().bg(color).black().bold(),
+    )];
+    if let Some(since) = data.since {
+        spans.push(Span::raw(format!(
+            "  since {} ({})",
+            since.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"),
+            (Utc::now() - since).humanize()
+        )));
+    }
+    spans.push(Span::raw(format!("  |  Outages: {}", data.outages)));
+
+    let block = Block::bordered().title(" netcheck - press CTRL-C to stop ");
+    frame.render_widget(Paragraph::new(Line::from(spans)).block(block), area);
+}
+
+fn render_target(frame: &mut Frame, area: Rect, name: &str, stats: &TargetStats) {
+    let block = Block::bordered().title(format!(" {name} "));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let [info, sparkline] =
+        Layout::horizontal([Constraint::Length(TARGET_INFO_WIDTH), Constraint::Min(0)])
+            .areas(inner);
+
+    let status = match stats.up {
+        true => Span::styled("up  ", Style::new().green().bold()),
+        false => Span::styled("down", Style::new().red().bold()),
+    };
+    let detail = match (stats.last_latency, &stats.last_error) {
+        (Some(latency), _) => format!(" {} ms", latency.as_millis()),
+        (None, Some(error)) => format!(" {error}"),
+        (None, None) => String::new(),
+    };
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![status, Span::raw(detail)])),
+        info,
+    );
+
+    // Only the latest checks fit, failed checks leave a gap.
+    let skip = stats.history.len().saturating_sub(sparkline.width as usize);
+    let history = stats.history.iter().skip(skip).collect::<Vec<_>>();
+    frame.render_widget(Sparkline::default().data(history).cyan(), sparkline);
+}
+
+fn render_outages(frame: &mut Frame, area: Rect, data: &StatusData) {
+    let items = data
+        .recent_outages
+        .iter()
+        .map(|outage| {
+            let start = outage
+                .start
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M:%S");
+
+            match outage.end {
+                Some(end) => ListItem::new(format!(
+                    "{start} - {} for {}",
+                    end.with_timezone(&Local).format("%H:%M:%S"),
+                    (end - outage.start).humanize()
+                )),
+                None => ListItem::new(format!(
+                    "{start} - ongoing for {}",
+                    (Utc::now() - outage.start).humanize()
+                ))
+                .red(),
+            }
+        })
+        .collect::<Vec<ListItem>>();
+
+    let block = Block::bordered().title(" Recent outages ");
+    frame.render_widget(List::new(items).block(block), area);
+}
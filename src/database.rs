@@ -0,0 +1,176 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDate, SecondsFormat, TimeZone, Utc};
+use rusqlite::{Connection, params};
+use serde_json::Value;
+
+use crate::log::Entry;
+
+/// Entries stored per transaction on import, so a monitor writing into the same database isn't
+/// locked out until a whole logfile is imported.
+const IMPORT_BATCH: usize = 1000;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS entries (
+        id INTEGER PRIMARY KEY,
+        timestamp TEXT NOT NULL,
+        message TEXT NOT NULL,
+        kind TEXT NOT NULL,
+        connectivity TEXT,
+        avg_latency_ms INTEGER,
+        failed_targets TEXT,
+        data TEXT NOT NULL
+    );
+    CREATE UNIQUE INDEX IF NOT EXISTS entries_timestamp_message ON entries (timestamp, message);
+    CREATE INDEX IF NOT EXISTS entries_kind_timestamp ON entries (kind, timestamp);
+";
+
+/// SQLite database the log entries are stored in next to the logfiles. Every entry is stored as
+/// JSON together with its connectivity, average latency and failed targets for ad-hoc queries.
+/// Entries are unique by timestamp and message, so importing a logfile twice adds nothing.
+pub struct Database {
+    path: PathBuf,
+    connection: Connection,
+}
+
+impl Database {
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        let connection = Connection::open(path)
+            .with_context(|| format!("Failed to open database '{}'", path.display()))?;
+        // Reports can read while the monitor writes.
+        connection.pragma_update(None, "journal_mode", "WAL")?;
+        connection.busy_timeout(std::time::Duration::from_secs(5))?;
+        connection.execute_batch(SCHEMA)?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            connection,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Stores a log entry. Returns false if it has no timestamp or is already stored.
+    pub fn insert(&self, data: &Value) -> Result<bool> {
+        insert(&self.connection, data)
+    }
+
+    /// Stores all entries in transactions of `IMPORT_BATCH` entries. Returns the number of entries
+    /// stored and the number of entries that were already stored.
+    pub fn import(&mut self, entries: impl Iterator<Item = Value>) -> Result<(usize, usize)> {
+        let mut entries = entries.peekable();
+        let (mut imported, mut skipped) = (0, 0);
+
+        while entries.peek().is_some() {
+            let transaction = self.connection.transaction()?;
+            for entry in entries.by_ref().take(IMPORT_BATCH) {
+                match insert(&transaction, &entry)? {
+                    true => imported += 1,
+                    false => skipped += 1,
+                }
+            }
+            transaction.commit()?;
+        }
+
+        Ok((imported, skipped))
+    }
+
+    /// Local days with any entry, the newest first.
+    pub fn days(&self) -> Result<Vec<NaiveDate>> {
+        let mut statement = self.connection.prepare(
+            "SELECT DISTINCT date(substr(timestamp, 1, 19), 'localtime') AS day
+                FROM entries ORDER BY day DESC",
+        )?;
+        let days = statement
+            .query_map([], |row| row.get::<_, String>(0))?
+            .filter_map(|day| day.ok())
+            .filter_map(|day| NaiveDate::parse_from_str(&day, "%Y-%m-%d").ok())
+            .collect();
+
+        Ok(days)
+    }
+
+    /// Checks, heartbeats and clock jumps of a local day, in the order they were logged.
+    pub fn entries_of_day(&self, day: NaiveDate) -> Result<Vec<Value>> {
+        let start = local_midnight(day);
+        let end = local_midnight(day + chrono::Days::new(1));
+        let mut statement = self.connection.prepare(
+            "SELECT data FROM entries
+                WHERE kind IN ('result', 'heartbeat', 'clock_jump')
+                    AND timestamp >= ?1 AND timestamp < ?2
+                ORDER BY timestamp",
+        )?;
+        let entries = statement
+            .query_map(params![start, end], |row| row.get::<_, String>(0))?
+            .filter_map(|data| data.ok())
+            .filter_map(|data| serde_json::from_str::<Value>(&data).ok())
+            .collect();
+
+        Ok(entries)
+    }
+}
+
+fn insert(connection: &Connection, data: &Value) -> Result<bool> {
+    let Some(timestamp) = data
+        .get("timestamp")
+        .and_then(|timestamp| serde_json::from_value::<DateTime<Utc>>(timestamp.clone()).ok())
+    else {
+        return Ok(false);
+    };
+    let kind = ["result", "clock_jump", "heartbeat"]
+        .into_iter()
+        .find(|kind| data.get(kind).is_some())
+        .unwrap_or("event");
+    let entry = Entry::from(data.clone());
+    let failed_targets = match entry.failing.is_empty() {
+        true => None,
+        false => Some(
+            entry
+                .failing
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .collect::<Vec<_>>()
+                .join(","),
+        ),
+    };
+
+    let inserted = connection.execute(
+        "INSERT OR IGNORE INTO entries
+            (timestamp, message, kind, connectivity, avg_latency_ms, failed_targets, data)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            format_timestamp(&timestamp),
+            entry.message,
+            kind,
+            entry.connectivity,
+            entry.avg.map(|avg| avg.as_millis() as i64),
+            failed_targets,
+            data.to_string(),
+        ],
+    )?;
+
+    Ok(inserted > 0)
+}
+
+/// Timestamps are stored with a fixed number of digits, so they sort as text.
+fn format_timestamp(timestamp: &DateTime<Utc>) -> String {
+    timestamp.to_rfc3339_opts(SecondsFormat::Nanos, true)
+}
+
+fn local_midnight(day: NaiveDate) -> String {
+    let midnight = day.and_hms_opt(0, 0, 0).unwrap_or_default();
+    let timestamp = Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .map(|timestamp| timestamp.with_timezone(&Utc))
+        .unwrap_or_else(|| Utc.from_utc_datetime(&midnight));
+
+    format_timestamp(&timestamp)
+}
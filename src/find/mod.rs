@@ -7,6 +7,9 @@ use anyhow::Result;
 
 use crate::{
     app::find::{FindAction, FindArgs},
+    archive,
+    config::Config,
+    database::Database,
     model::Report,
     project::Project,
 };
@@ -14,13 +17,19 @@ use crate::{
 mod longest;
 mod most_outages;
 
-pub fn run(args: FindArgs, project: Project) -> Result<()> {
-    let logdir = match args.dir.as_deref() {
-        Some(path) => path,
-        None => project.log_dir(),
-    };
-    let logfiles = collect_all_logfiles(logdir)?;
-    let report = Report::from_path_bufs(logfiles, None);
+pub fn run(args: FindArgs, config: Config, project: Project) -> Result<()> {
+    let report = match config.database(&project) {
+        Some(path) => {
+            let database = Database::open(&path)?;
+            Report::from_database(&database, &database.days()?, None)?
+        }
+        None => Report::from_path_bufs(collect_all_logfiles(config.log_dir(&project))?, None),
+    }
+    .with_debounce(config.debounce.clone())
+    .with_gap_threshold(config.report.gap_threshold);
+    if report.unreadable() > 0 {
+        eprintln!("Skipped {} unreadable log entries", report.unreadable());
+    }
 
     match args.action {
         FindAction::Longest => longest::run(report),
@@ -36,7 +45,7 @@ fn collect_all_logfiles<P: AsRef<Path>>(dir: P) -> Result<Vec<PathBuf>> {
         .filter_map(|e| {
             let path = e.path();
 
-            if path.extension().and_then(|x| x.to_str()) == Some("jsonl") {
+            if archive::is_logfile(&path) {
                 Some(path)
             } else {
                 None
@@ -0,0 +1,58 @@
+use std::{fs::read_dir, io::BufRead};
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+
+use crate::{
+    app::import::ImportArgs, archive, config::Config, database::Database, project::Project, schema,
+    sort::sort_by_filename_date,
+};
+
+/// Backfills the database from logfiles. Entries that are already stored are skipped, so
+/// logfiles can be imported again, e.g. the current one of a monitor that writes into the
+/// database as well.
+pub fn run(args: ImportArgs, config: Config, project: Project) -> Result<()> {
+    let path = config.database(&project).ok_or(anyhow::anyhow!(
+        "No database set, use --database or 'database' in [log] of the config"
+    ))?;
+    let mut database = Database::open(&path)?;
+
+    let mut logfiles = match args.files.is_empty() {
+        true => read_dir(config.log_dir(&project))?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| archive::is_logfile(path))
+            .collect(),
+        false => args.files,
+    };
+    sort_by_filename_date(&mut logfiles, |path| path.to_str().unwrap_or(""));
+    logfiles.reverse();
+
+    let (mut imported, mut skipped) = (0, 0);
+    for logfile in logfiles {
+        let reader = archive::open(&logfile)
+            .with_context(|| format!("Failed to open logfile '{}'", logfile.display()))?;
+        let entries = reader
+            .split(b'\n')
+            .map_while(|line| line.ok())
+            .filter_map(|line| serde_json::from_slice::<Value>(&line).ok())
+            .filter_map(|entry| schema::migrate(entry).ok());
+        let (file_imported, file_skipped) = database
+            .import(entries)
+            .with_context(|| format!("Failed to import logfile '{}'", logfile.display()))?;
+
+        println!(
+            "Imported {file_imported} entries from '{}' ({file_skipped} already stored)",
+            logfile.display()
+        );
+        imported += file_imported;
+        skipped += file_skipped;
+    }
+
+    println!(
+        "Imported {imported} entries into '{}' ({skipped} already stored)",
+        path.display()
+    );
+
+    Ok(())
+}
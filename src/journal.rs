@@ -0,0 +1,158 @@
+use std::{ffi::OsStr, path::Path};
+
+use anyhow::Result;
+
+use crate::{log::Entry, systemd};
+
+pub const JOURNALD_SOCKET: &str = "/run/systemd/journal/socket";
+pub const SYSLOG_SOCKET: &str = "/dev/log";
+
+const IDENTIFIER: &str = "netcheck";
+/// Facility 'daemon' of RFC 5424.
+const SYSLOG_FACILITY: u8 = 3;
+/// Private enterprise number reserved for documentation, used for the structured data id.
+const SYSLOG_ENTERPRISE: u32 = 32473;
+
+/// Sends an entry to the journal with the native protocol. The check result is sent as the
+/// fields `CONNECTIVITY`, `AVG_LATENCY_MS` and `FAILED_TARGETS`, every other field of the entry
+/// prefixed with `NETCHECK_`.
+pub fn send_journald(socket: Option<&Path>, entry: &Entry) -> Result<()> {
+    let mut data = Vec::new();
+
+    append_journal_field(&mut data, "MESSAGE", &entry.message);
+    append_journal_field(&mut data, "PRIORITY", &severity(entry).to_string());
+    append_journal_field(&mut data, "SYSLOG_IDENTIFIER", IDENTIFIER);
+    if let Some(connectivity) = &entry.connectivity {
+        append_journal_field(&mut data, "CONNECTIVITY", connectivity);
+    }
+    if let Some(avg) = entry.avg {
+        append_journal_field(&mut data, "AVG_LATENCY_MS", &avg.as_millis().to_string());
+    }
+    if !entry.failing.is_empty() {
+        append_journal_field(
+            &mut data,
+            "FAILED_TARGETS",
+            &entry.failing_targets().join(", "),
+        );
+    }
+    for (key, value) in entry.flat_fields() {
+        append_journal_field(&mut data, &journal_field_name(&key), &value);
+    }
+
+    let socket = socket.map_or(OsStr::new(JOURNALD_SOCKET), Path::as_os_str);
+    systemd::send(socket, &data)
+        .map_err(|err| anyhow::anyhow!("Failed to send to the journal via {socket:?}: {err}"))
+}
+
+/// Sends an entry to syslog as RFC 5424 message, with the check result as structured data.
+pub fn send_syslog(socket: Option<&Path>, entry: &Entry) -> Result<()> {
+    let priority = SYSLOG_FACILITY * 8 + severity(entry);
+    let timestamp = entry
+        .timestamp
+        .map(|timestamp| timestamp.to_rfc3339())
+        .unwrap_or("-".into());
+    let hostname = std::fs::read_to_string("/proc/sys/kernel/hostname")
+        .ok()
+        .map(|hostname| hostname.trim().to_string())
+        .filter(|hostname| !hostname.is_empty())
+        .unwrap_or("-".into());
+
+    let mut params = Vec::new();
+    if let Some(connectivity) = &entry.connectivity {
+        params.push(("connectivity".to_string(), connectivity.clone()));
+    }
+    if let Some(avg) = entry.avg {
+        params.push(("avg_latency_ms".to_string(), avg.as_millis().to_string()));
+    }
+    if !entry.failing.is_empty() {
+        params.push((
+            "failed_targets".to_string(),
+            entry.failing_targets().join(", "),
+        ));
+    }
+    params.extend(
+        entry
+            .flat_fields()
+            .into_iter()
+            .map(|(key, value)| (syslog_param_name(&key), value)),
+    );
+    let structured_data = match params.is_empty() {
+        true => "-".to_string(),
+        false => {
+            let params = params
+                .iter()
+                .map(|(name, value)| format!(" {name}=\"{}\"", escape_param_value(value)))
+                .collect::<String>();
+            format!("[{IDENTIFIER}@{SYSLOG_ENTERPRISE}{params}]")
+        }
+    };
+
+    let message = format!(
+        "<{priority}>1 {timestamp} {hostname} {IDENTIFIER} {} - {structured_data} {}",
+        std::process::id(),
+        entry.message
+    );
+
+    let socket = socket.map_or(OsStr::new(SYSLOG_SOCKET), Path::as_os_str);
+    systemd::send(socket, message.as_bytes())
+        .map_err(|err| anyhow::anyhow!("Failed to send to syslog via {socket:?}: {err}"))
+}
+
+/// Syslog severity: errors, offline and degraded checks stand out from the other entries.
+fn severity(entry: &Entry) -> u8 {
+    if entry.fields.contains_key("error") {
+        return 3;
+    }
+
+    match entry.connectivity.as_deref() {
+        Some("Offline") => 4,
+        Some("Degraded") => 5,
+        _ => 6,
+    }
+}
+
+/// Appends `KEY=value`, or the length-prefixed form if the value spans several lines.
+fn append_journal_field(data: &mut Vec<u8>, key: &str, value: &str) {
+    data.extend_from_slice(key.as_bytes());
+    match value.contains('\n') {
+        true => {
+            data.push(b'\n');
+            data.extend_from_slice(&(value.len() as u64).to_le_bytes());
+        }
+        false => data.push(b'='),
+    }
+    data.extend_from_slice(value.as_bytes());
+    data.push(b'\n');
+}
+
+/// Journal field names only consist of uppercase letters, digits and underscores.
+fn journal_field_name(key: &str) -> String {
+    let key = key
+        .chars()
+        .map(|char| match char.is_ascii_alphanumeric() {
+            true => char.to_ascii_uppercase(),
+            false => '_',
+        })
+        .collect::<String>();
+
+    format!("NETCHECK_{key}")
+}
+
+/// Parameter names are printable ASCII without '=', ' ', ']' and '"', of at most 32 characters.
+fn syslog_param_name(key: &str) -> String {
+    key.chars()
+        .map(|char| match char {
+            '=' | ' ' | ']' | '"' => '_',
+            char if char.is_ascii_graphic() => char,
+            _ => '_',
+        })
+        .take(32)
+        .collect()
+}
+
+fn escape_param_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace(']', "\\]")
+}
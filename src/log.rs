@@ -1,14 +1,28 @@
 use std::{
     fs::{File, OpenOptions, metadata},
-    io::Write,
+    io::{IsTerminal, Write},
     path::{Path, PathBuf},
-    sync::Mutex,
+    sync::{
+        Arc, Mutex, RwLock,
+        mpsc::{Receiver, SyncSender, TrySendError, sync_channel},
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
 };
 
-use anyhow::Result;
-use chrono::Local;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, Utc};
 use clap::ValueEnum;
-use serde::Serialize;
+use ratatui::crossterm::style::{Color, Stylize};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+
+use crate::{
+    archive::{self, Compression},
+    database::Database,
+    journal,
+    schema::SCHEMA_VERSION,
+};
 
 #[macro_export]
 macro_rules! log {
@@ -18,6 +32,7 @@ macro_rules! log {
             let data = serde_json::json!({
                 "timestamp": chrono::Utc::now(),
                 "message": $msg,
+                "schema_version": $crate::schema::SCHEMA_VERSION,
                 $(
                     stringify!($key): $crate::log_val!($key $(, $val)?)
                 ),*
@@ -31,6 +46,7 @@ macro_rules! log {
         let data = serde_json::json!({
             "timestamp": chrono::Utc::now(),
             "message": $msg,
+            "schema_version": $crate::schema::SCHEMA_VERSION,
         });
         logger_ref.log(data)
     }};
@@ -50,21 +66,203 @@ macro_rules! log_val {
 pub const DEFAULT_FILE_PREFIX: &str = "netcheck";
 pub const DEFAULT_MAX_SIZE: u64 = 2 * 1024 * 1024;
 pub const DEFAULT_LOG_MODE: LogMode = LogMode::All;
+pub const DEFAULT_CONSOLE_FORMAT: ConsoleFormat = ConsoleFormat::Json;
+pub const DEFAULT_COMPRESSION: Compression = Compression::None;
+/// How often a failing sink besides the logfile is reported at most.
+const SINK_ERROR_INTERVAL: Duration = Duration::from_secs(60);
+/// Entries waiting to be stored in the database, beyond which new ones are only logged to the
+/// other sinks.
+const DATABASE_QUEUE: usize = 1000;
 
 pub struct Logger {
+    settings: RwLock<LoggerSettings>,
+    state: Mutex<Option<LoggerState>>,
+    database: Mutex<Option<DatabaseWriter>>,
+    /// When a failing sink besides the logfile was last reported.
+    sink_error_at: Mutex<Option<Instant>>,
+    compressing: Arc<Mutex<Compressing>>,
+}
+
+/// Compression of rotated logfiles, which runs in the background.
+#[derive(Default)]
+struct Compressing {
+    running: bool,
+    /// The latest rotation, whose files are compressed next.
+    pending: Option<Rotation>,
+    /// Entries about files that failed to compress, to be logged with the next entry.
+    failures: Vec<Value>,
+}
+
+struct Rotation {
+    dir: PathBuf,
+    prefix: String,
+    current: PathBuf,
+    compression: Compression,
+}
+
+impl Compressing {
+    /// Compresses the files of pending rotations, until there are none left. The files are listed
+    /// while the lock is held, so a rotation can't create its new file in the meantime.
+    fn run(compressing: &Mutex<Compressing>) {
+        loop {
+            let (rotation, rotated) = {
+                let Ok(mut compressing) = compressing.lock() else {
+                    return;
+                };
+                let Some(rotation) = compressing.pending.take() else {
+                    compressing.running = false;
+                    return;
+                };
+                let rotated = archive::rotated(&rotation.dir, &rotation.prefix, &rotation.current);
+
+                (rotation, rotated)
+            };
+
+            let failures = match rotated {
+                Ok(rotated) => rotated
+                    .iter()
+                    .filter_map(|path| {
+                        let err = archive::compress(path, rotation.compression).err()?;
+                        Some(compression_failure(path, &err))
+                    })
+                    .collect(),
+                Err(err) => vec![compression_failure(&rotation.dir, &err)],
+            };
+
+            if let Ok(mut compressing) = compressing.lock() {
+                compressing.failures.extend(failures);
+            }
+        }
+    }
+}
+
+/// Stores the entries in the database on a thread of its own, so a database that is busy, e.g. with
+/// an import, doesn't hold up the checks on the runtime.
+struct DatabaseWriter {
+    entries: Option<SyncSender<(PathBuf, Value)>>,
+    /// The latest error of the thread, to be reported with the next entry.
+    error: Arc<Mutex<Option<anyhow::Error>>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl DatabaseWriter {
+    fn spawn() -> Result<Self> {
+        let (sender, receiver) = sync_channel(DATABASE_QUEUE);
+        let error = Arc::default();
+        let thread_error = Arc::clone(&error);
+        let thread = std::thread::Builder::new()
+            .name("database".into())
+            .spawn(move || Self::run(receiver, &thread_error))
+            .context("Failed to start the database writer")?;
+
+        Ok(Self {
+            entries: Some(sender),
+            error,
+            thread: Some(thread),
+        })
+    }
+
+    /// Stores the entries until the writer is dropped. The database is opened with the first entry
+    /// and again once its path changes.
+    fn run(entries: Receiver<(PathBuf, Value)>, error: &Mutex<Option<anyhow::Error>>) {
+        let mut database: Option<Database> = None;
+
+        for (path, entry) in entries {
+            let stored = match database.as_ref() {
+                Some(database) if database.path() == path => Ok(database),
+                _ => Database::open(&path).map(|opened| &*database.insert(opened)),
+            }
+            .and_then(|database| database.insert(&entry))
+            .with_context(|| format!("Failed to store log entry in '{}'", path.display()));
+
+            if let (Err(err), Ok(mut error)) = (stored, error.lock()) {
+                *error = Some(err);
+            }
+        }
+    }
+
+    fn send(&self, path: &Path, entry: Value) -> Result<()> {
+        let Some(entries) = &self.entries else {
+            return Ok(());
+        };
+
+        match entries.try_send((path.to_path_buf(), entry)) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(_)) => Err(anyhow::anyhow!(
+                "Database '{}' falls behind, log entry was not stored",
+                path.display()
+            )),
+            Err(TrySendError::Disconnected(_)) => Err(anyhow::anyhow!(
+                "Database writer has stopped, log entry was not stored in '{}'",
+                path.display()
+            )),
+        }
+    }
+
+    fn take_error(&self) -> Option<anyhow::Error> {
+        self.error.lock().ok()?.take()
+    }
+}
+
+impl Drop for DatabaseWriter {
+    /// Stores the entries still queued before the monitor exits.
+    fn drop(&mut self) {
+        drop(self.entries.take());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+struct LoggerSettings {
     dir: PathBuf,
     file_prefix: String,
     max_size: u64,
-    state: Mutex<Option<LoggerState>>,
     mode: LogMode,
+    console: ConsoleFormat,
+    socket: Option<PathBuf>,
+    compression: Compression,
+    database: Option<PathBuf>,
+    /// Whether anything may be printed to the terminal besides the log lines. False while the
+    /// dashboard draws on it.
+    terminal: bool,
 }
 
-#[derive(ValueEnum, Debug, Clone)]
+#[derive(ValueEnum, Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "lowercase")]
 pub enum LogMode {
     Silent,
     Stdout,
     File,
     All,
+    /// Logs into files and sends every entry with structured fields to the systemd journal.
+    Journald,
+    /// Logs into files and sends every entry to syslog, as RFC 5424 message over a Unix socket.
+    Syslog,
+}
+
+/// Format of the lines printed to the terminal. Logfiles are always written as JSONL.
+#[derive(ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConsoleFormat {
+    /// The same JSON line that is written into the logfile.
+    Json,
+    /// One coloured line with the local time, the message, the connectivity, the average latency
+    /// and the failing targets.
+    Pretty,
+    /// Like 'pretty', but without colours and alignment, e.g. for other tools to grep through.
+    Compact,
+}
+
+impl LogMode {
+    /// Keeps logging into files, but nothing to the terminal.
+    pub fn without_stdout(&self) -> LogMode {
+        match self {
+            LogMode::Silent | LogMode::Stdout => LogMode::Silent,
+            LogMode::File | LogMode::All => LogMode::File,
+            LogMode::Journald | LogMode::Syslog => self.clone(),
+        }
+    }
 }
 
 impl Logger {
@@ -73,36 +271,140 @@ impl Logger {
     }
 
     pub fn log(&self, data: impl Serialize) -> Result<()> {
-        match self.mode {
-            LogMode::Stdout => self.log_stdout(&data),
-            LogMode::File => self.log_file(&data),
-            LogMode::All => self.log_all(&data),
-            LogMode::Silent => Ok(()),
+        let settings = self
+            .settings
+            .read()
+            .map_err(|_| anyhow::anyhow!("Lock poisened"))?;
+
+        match settings.mode {
+            LogMode::Stdout => Self::log_stdout(&settings, &data)?,
+            LogMode::File => self.log_file(&settings, &data)?,
+            LogMode::All => self.log_all(&settings, &data)?,
+            LogMode::Journald | LogMode::Syslog => self.log_system(&settings, &data)?,
+            LogMode::Silent => {}
+        }
+
+        // Like the journal, the database may fail without ending the monitor.
+        if let Some(path) = &settings.database
+            && let Err(err) = self.log_database(path, &data)
+        {
+            self.report_sink_error(&err);
+        }
+        drop(settings);
+
+        let failures = match self.compressing.lock() {
+            Ok(mut compressing) => std::mem::take(&mut compressing.failures),
+            Err(_) => Vec::new(),
+        };
+        for failure in failures {
+            self.log(failure)?;
+        }
+
+        Ok(())
+    }
+
+    /// Applies new settings, e.g. after the config was reloaded. The next entry is written into
+    /// the logfile that matches the new settings.
+    pub fn reload(&self, builder: LoggerBuilder) -> Result<()> {
+        let settings = builder
+            .build()?
+            .settings
+            .into_inner()
+            .map_err(|_| anyhow::anyhow!("Lock poisened"))?;
+
+        *self
+            .settings
+            .write()
+            .map_err(|_| anyhow::anyhow!("Lock poisened"))? = settings;
+
+        let mut lock = self
+            .state
+            .lock()
+            .map_err(|_| anyhow::anyhow!("Mutex poisened"))?;
+        if let Some(old_state) = lock.take() {
+            old_state.file.sync_all()?;
         }
+
+        Ok(())
     }
 
-    fn log_all(&self, data: impl Serialize) -> Result<()> {
-        self.log_file(&data)?;
-        self.log_stdout(&data)?;
+    fn log_all(&self, settings: &LoggerSettings, data: impl Serialize) -> Result<()> {
+        self.log_file(settings, &data)?;
+        Self::log_stdout(settings, &data)?;
 
         Ok(())
     }
 
-    fn log_stdout(&self, data: impl Serialize) -> Result<()> {
-        let content = serde_json::to_string(&data)?;
+    /// Queues the entry for the database next to the logfile. The writer starts with the first
+    /// entry, errors it ran into since the last entry are returned.
+    fn log_database(&self, path: &Path, data: impl Serialize) -> Result<()> {
+        let mut lock = self
+            .database
+            .lock()
+            .map_err(|_| anyhow::anyhow!("Mutex poisened"))?;
+
+        let writer = match lock.as_mut() {
+            Some(writer) => writer,
+            None => lock.insert(DatabaseWriter::spawn()?),
+        };
+        if let Some(err) = writer.take_error() {
+            self.report_sink_error(&err);
+        }
+
+        writer.send(path, serde_json::to_value(&data)?)
+    }
+
+    /// Only the logfile is required, the journal or syslog may fail without ending the monitor.
+    fn log_system(&self, settings: &LoggerSettings, data: impl Serialize) -> Result<()> {
+        self.log_file(settings, &data)?;
+
+        let entry = Entry::from(serde_json::to_value(&data)?);
+        let sent = match settings.mode {
+            LogMode::Syslog => journal::send_syslog(settings.socket.as_deref(), &entry),
+            _ => journal::send_journald(settings.socket.as_deref(), &entry),
+        };
+        if let Err(err) = sent {
+            self.report_sink_error(&err);
+        }
+
+        Ok(())
+    }
+
+    /// Prints the error of a sink besides the logfile, at most once per `SINK_ERROR_INTERVAL`, so a
+    /// sink that keeps failing doesn't flood the terminal.
+    fn report_sink_error(&self, err: &anyhow::Error) {
+        let Ok(mut reported_at) = self.sink_error_at.lock() else {
+            return;
+        };
+
+        if reported_at.is_none_or(|at| at.elapsed() >= SINK_ERROR_INTERVAL) {
+            *reported_at = Some(Instant::now());
+            self.print_error(&format!("{err:#}"));
+        }
+    }
+
+    fn log_stdout(settings: &LoggerSettings, data: impl Serialize) -> Result<()> {
+        let content = match settings.console {
+            ConsoleFormat::Json => serde_json::to_string(&data)?,
+            ConsoleFormat::Pretty => format_line(
+                serde_json::to_value(&data)?,
+                std::io::stdout().is_terminal(),
+            ),
+            ConsoleFormat::Compact => format_line(serde_json::to_value(&data)?, false),
+        };
         println!("{content}");
 
         Ok(())
     }
 
-    fn log_file(&self, data: impl Serialize) -> Result<()> {
-        let target_path = self.get_current_file_path()?;
+    fn log_file(&self, settings: &LoggerSettings, data: impl Serialize) -> Result<()> {
+        let target_path = settings.get_current_file_path()?;
         let mut lock = self
             .state
             .lock()
             .map_err(|_| anyhow::anyhow!("Mutex poisened"))?;
         let needs_new_file = match &*lock {
-            Some(state) => state.path != target_path || state.current_size >= self.max_size,
+            Some(state) => state.path != target_path || state.current_size >= settings.max_size,
             None => true,
         };
 
@@ -111,10 +413,14 @@ impl Logger {
                 let _ = old_state.file.sync_all();
             }
 
-            if !self.dir.exists() {
-                std::fs::create_dir_all(&self.dir)?;
+            if !settings.dir.exists() {
+                std::fs::create_dir_all(&settings.dir)?;
             }
 
+            // Rotated files are compressed, including those left behind by earlier runs. A file
+            // that fails to compress is kept as it is and retried on the next rotation.
+            self.compress_rotated(settings, &target_path);
+
             let file = OpenOptions::new()
                 .create(true)
                 .append(true)
@@ -139,6 +445,55 @@ impl Logger {
         Ok(())
     }
 
+    /// Compresses the rotated logfiles in the background, so writing entries isn't held up by it.
+    /// Must be called before `current` is created, so it's never taken for a rotated file.
+    fn compress_rotated(&self, settings: &LoggerSettings, current: &Path) {
+        if settings.compression == Compression::None {
+            return;
+        }
+
+        let Ok(mut compressing) = self.compressing.lock() else {
+            return;
+        };
+        compressing.pending = Some(Rotation {
+            dir: settings.dir.clone(),
+            prefix: settings.file_prefix.clone(),
+            current: current.to_path_buf(),
+            compression: settings.compression,
+        });
+        if std::mem::replace(&mut compressing.running, true) {
+            return;
+        }
+        drop(compressing);
+
+        let compressing = Arc::clone(&self.compressing);
+        let task = move || Compressing::run(&compressing);
+        match tokio::runtime::Handle::try_current() {
+            Ok(runtime) => drop(runtime.spawn_blocking(task)),
+            Err(_) => task(),
+        }
+    }
+
+    /// Whether notes and errors may be printed to the terminal.
+    pub fn has_terminal(&self) -> bool {
+        self.settings.read().is_ok_and(|settings| settings.terminal)
+    }
+
+    /// Prints a note to stdout, unless the terminal is taken by the dashboard.
+    pub fn print(&self, note: &str) {
+        if self.has_terminal() {
+            println!("{note}");
+        }
+    }
+
+    /// Prints an error that can't be logged to stderr, unless the terminal is taken by the
+    /// dashboard.
+    pub fn print_error(&self, error: &str) {
+        if self.has_terminal() {
+            eprintln!("{error}");
+        }
+    }
+
     pub fn sync(&self) -> Result<()> {
         let mut lock = self
             .state
@@ -150,7 +505,9 @@ impl Logger {
 
         Ok(())
     }
+}
 
+impl LoggerSettings {
     fn get_current_file_path(&self) -> std::io::Result<PathBuf> {
         let date_str = Local::now().format("%Y-%m-%d").to_string();
         let mut index = 0;
@@ -159,6 +516,11 @@ impl Logger {
             let filename = format!("{}_{}_{}.jsonl", self.file_prefix, date_str, index);
             let path = self.dir.join(filename);
 
+            if archive::is_archived(&path) {
+                index += 1;
+                continue;
+            }
+
             if !path.exists() {
                 return Ok(path);
             }
@@ -178,6 +540,11 @@ pub struct LoggerBuilder {
     dir: Option<PathBuf>,
     max_size: Option<u64>,
     mode: Option<LogMode>,
+    console: Option<ConsoleFormat>,
+    socket: Option<PathBuf>,
+    compression: Option<Compression>,
+    database: Option<PathBuf>,
+    terminal: Option<bool>,
 }
 
 impl LoggerBuilder {
@@ -205,6 +572,39 @@ impl LoggerBuilder {
         self
     }
 
+    pub fn with_console_format(mut self, console: ConsoleFormat) -> Self {
+        self.console = Some(console);
+
+        self
+    }
+
+    /// Sets the socket of the journal or syslog, instead of the default one.
+    pub fn with_socket(mut self, socket: Option<PathBuf>) -> Self {
+        self.socket = socket;
+
+        self
+    }
+
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = Some(compression);
+
+        self
+    }
+
+    /// Stores the entries in a SQLite database as well.
+    pub fn with_database(mut self, database: Option<PathBuf>) -> Self {
+        self.database = database;
+
+        self
+    }
+
+    /// Without the terminal, notes and errors that are not log entries are not printed.
+    pub fn with_terminal(mut self, terminal: bool) -> Self {
+        self.terminal = Some(terminal);
+
+        self
+    }
+
     pub fn build(self) -> Result<Logger> {
         let dir = self.dir.ok_or(anyhow::anyhow!(
             "Log directory is required, but was not set!"
@@ -213,23 +613,234 @@ impl LoggerBuilder {
         let max_size = self.max_size.unwrap_or(DEFAULT_MAX_SIZE);
         let state = Mutex::new(None);
         let mode = self.mode.unwrap_or(DEFAULT_LOG_MODE);
+        let console = self.console.unwrap_or(DEFAULT_CONSOLE_FORMAT);
+        let compression = self.compression.unwrap_or(DEFAULT_COMPRESSION);
 
         Ok(Logger {
-            dir,
-            file_prefix,
-            max_size,
+            settings: RwLock::new(LoggerSettings {
+                dir,
+                file_prefix,
+                max_size,
+                mode,
+                console,
+                socket: self.socket,
+                compression,
+                database: self.database,
+                terminal: self.terminal.unwrap_or(true),
+            }),
             state,
-            mode,
+            database: Mutex::new(None),
+            sink_error_at: Mutex::new(None),
+            compressing: Arc::default(),
         })
     }
 }
 
+fn compression_failure(path: &Path, err: &std::io::Error) -> Value {
+    json!({
+        "timestamp": Utc::now(),
+        "message": "Failed to compress logfile",
+        "schema_version": SCHEMA_VERSION,
+        "file": path,
+        "error": err.to_string(),
+    })
+}
+
 struct LoggerState {
     file: File,
     path: PathBuf,
     current_size: u64,
 }
 
+/// The parts of a log entry that are shown in the terminal and sent to the journal or syslog.
+pub struct Entry {
+    pub timestamp: Option<DateTime<Local>>,
+    pub message: String,
+    pub connectivity: Option<String>,
+    pub avg: Option<Duration>,
+    /// Names of the failing targets together with their error.
+    pub failing: Vec<(String, Option<String>)>,
+    /// Every other field of the entry.
+    pub fields: Map<String, Value>,
+}
+
+impl From<Value> for Entry {
+    fn from(data: Value) -> Self {
+        let mut fields = match data {
+            Value::Object(fields) => fields,
+            data => {
+                return Entry {
+                    timestamp: None,
+                    message: data.to_string(),
+                    connectivity: None,
+                    avg: None,
+                    failing: Vec::new(),
+                    fields: Map::new(),
+                };
+            }
+        };
+        let timestamp = fields
+            .remove("timestamp")
+            .and_then(|timestamp| serde_json::from_value::<DateTime<Utc>>(timestamp).ok())
+            .map(|timestamp| timestamp.with_timezone(&Local));
+        fields.remove("schema_version");
+        let message = match fields.remove("message") {
+            Some(Value::String(message)) => message,
+            Some(message) => message.to_string(),
+            None => String::new(),
+        };
+        let result = match fields.remove("result") {
+            Some(Value::Object(result)) => result,
+            _ => Map::new(),
+        };
+        let connectivity = result
+            .get("connectivity")
+            .and_then(Value::as_str)
+            .map(String::from);
+        let avg = result
+            .get("avg")
+            .and_then(|avg| serde_json::from_value::<Duration>(avg.clone()).ok());
+        let failing = result
+            .get("results")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter(|target| target.get("success").and_then(Value::as_bool) == Some(false))
+            .map(|target| {
+                let name = target.get("target").and_then(Value::as_str).unwrap_or("?");
+                let error = match target.get("error") {
+                    Some(Value::String(error)) => Some(error.clone()),
+                    Some(Value::Null) | None => None,
+                    Some(error) => Some(error.to_string()),
+                };
+                (name.to_string(), error)
+            })
+            .collect();
+
+        Entry {
+            timestamp,
+            message,
+            connectivity,
+            avg,
+            failing,
+            fields,
+        }
+    }
+}
+
+impl Entry {
+    /// The failing targets as `name (error)`.
+    pub fn failing_targets(&self) -> Vec<String> {
+        self.failing
+            .iter()
+            .map(|(name, error)| match error {
+                Some(error) => format!("{name} ({error})"),
+                None => name.clone(),
+            })
+            .collect()
+    }
+
+    /// Every other field as key and value, nested objects flattened as `key.field`.
+    pub fn flat_fields(&self) -> Vec<(String, String)> {
+        let mut fields = Vec::new();
+        for (key, value) in &self.fields {
+            flatten_field(key.clone(), value, &mut fields);
+        }
+
+        fields
+    }
+}
+
+/// Formats a log entry as a single line. The check result is summarized by its connectivity,
+/// average latency and failing targets, every other field is appended as `key=value`.
+fn format_line(data: Value, colored: bool) -> String {
+    let entry = Entry::from(data);
+    let mut parts = format_result(&entry, colored);
+    parts.extend(
+        entry
+            .flat_fields()
+            .into_iter()
+            .map(|(key, value)| format!("{key}={value}")),
+    );
+
+    match colored {
+        true => {
+            let time = entry
+                .timestamp
+                .map(|timestamp| timestamp.format("%H:%M:%S").to_string())
+                .unwrap_or_default();
+            format!(
+                "{} {:<45} {}",
+                time.dark_grey(),
+                entry.message,
+                parts.join("  ")
+            )
+        }
+        false => {
+            let time = entry
+                .timestamp
+                .map(|timestamp| timestamp.format("%Y-%m-%dT%H:%M:%S").to_string())
+                .unwrap_or_default();
+            let mut line = format!("{time} {}", entry.message);
+            for part in parts {
+                line.push(' ');
+                line.push_str(&part);
+            }
+            line
+        }
+    }
+}
+
+fn flatten_field(key: String, value: &Value, fields: &mut Vec<(String, String)>) {
+    match value {
+        Value::Object(nested) => {
+            for (field, value) in nested {
+                flatten_field(format!("{key}.{field}"), value, fields);
+            }
+        }
+        Value::String(value) => fields.push((key, value.clone())),
+        value => fields.push((key, value.to_string())),
+    }
+}
+
+fn format_result(entry: &Entry, colored: bool) -> Vec<String> {
+    let mut parts = Vec::new();
+
+    if let Some(connectivity) = &entry.connectivity {
+        parts.push(match colored {
+            true => {
+                let color = match connectivity.as_str() {
+                    "Online" => Color::Green,
+                    "Degraded" => Color::Yellow,
+                    _ => Color::Red,
+                };
+                format!("{:<8}", connectivity)
+                    .with(color)
+                    .bold()
+                    .to_string()
+            }
+            false => format!("connectivity={connectivity}"),
+        });
+    }
+
+    if let Some(avg) = entry.avg {
+        parts.push(match colored {
+            true => format!("{:>5} ms", avg.as_millis()),
+            false => format!("avg={}ms", avg.as_millis()),
+        });
+    }
+
+    let failing = entry.failing_targets();
+    if !failing.is_empty() {
+        parts.push(match colored {
+            true => format!("failing: {}", failing.join(", ")).red().to_string(),
+            false => format!("failing={}", failing.join(",").replace(' ', "")),
+        });
+    }
+
+    parts
+}
+
 #[doc(hidden)]
 pub fn ensure_logger(logger: &Logger) -> &Logger {
     logger
@@ -8,18 +8,32 @@ use tokio::runtime::Builder;
 use crate::app::App;
 
 mod app;
+mod archive;
 mod check;
+mod config;
+mod dashboard;
+mod database;
 mod find;
+mod import;
+mod journal;
+mod migrate;
 mod model;
+mod proc;
 mod project;
-mod runner;
+mod prune;
+mod schema;
 mod sort;
+mod status;
+mod systemd;
 mod time;
 mod tracker;
 #[macro_use]
 mod log;
 mod monitor;
+mod notify;
 mod report;
+mod runner;
+mod server;
 
 fn main() {
     if let Err(err) = run() {
@@ -0,0 +1,125 @@
+use std::{fs::read_dir, io::BufRead, path::Path};
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+
+use crate::{
+    app::migrate::MigrateArgs,
+    archive,
+    config::Config,
+    proc,
+    project::Project,
+    schema::{self, SCHEMA_VERSION},
+    sort::sort_by_filename_date,
+};
+
+/// Rewrites logfiles written with an older schema in the current one. Logfiles a process still
+/// holds open are skipped, and so is the newest logfile of each prefix, since a monitor the open
+/// files can't be checked for may still write into it.
+pub fn run(args: MigrateArgs, config: Config, project: Project) -> Result<()> {
+    let mut logfiles = match args.files.is_empty() {
+        true => read_dir(config.log_dir(&project))?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| archive::is_logfile(path))
+            .collect(),
+        false => args.files,
+    };
+    sort_by_filename_date(&mut logfiles, |path| path.to_str().unwrap_or(""));
+    logfiles.reverse();
+
+    let newest = archive::newest_in_dirs(&logfiles);
+    let mut warned = false;
+    let mut total = Migration::default();
+    for logfile in logfiles {
+        if archive::is_newest(&logfile, &newest) {
+            println!(
+                "Skipping '{}': newest logfile, a monitor may still write into it",
+                logfile.display()
+            );
+            continue;
+        }
+        match proc::open_by(&logfile) {
+            Some(processes) => {
+                if let Some((pid, name)) = processes.first() {
+                    println!(
+                        "Skipping '{}': still open by process {pid} ({name})",
+                        logfile.display()
+                    );
+                    continue;
+                }
+            }
+            None if !warned => {
+                eprintln!("Warning: Can't check which logfiles are still open on this system");
+                warned = true;
+            }
+            None => {}
+        }
+
+        let migration = migrate(&logfile, args.dry_run)
+            .with_context(|| format!("Failed to migrate logfile '{}'", logfile.display()))?;
+        if migration.migrated > 0 || migration.unreadable > 0 {
+            println!("'{}': {migration}", logfile.display());
+        }
+        total.migrated += migration.migrated;
+        total.unreadable += migration.unreadable;
+    }
+
+    match args.dry_run {
+        true => println!("Would migrate to schema version {SCHEMA_VERSION}: {total}"),
+        false => println!("Migrated to schema version {SCHEMA_VERSION}: {total}"),
+    }
+
+    Ok(())
+}
+
+/// Entries migrated in a logfile, and entries that were kept as they are since they can't be
+/// read, e.g. as they were written by a newer release.
+#[derive(Debug, Default)]
+struct Migration {
+    migrated: usize,
+    unreadable: usize,
+}
+
+impl std::fmt::Display for Migration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} entries migrated, {} unreadable entries kept",
+            self.migrated, self.unreadable
+        )
+    }
+}
+
+/// Migrates all entries of a logfile. The logfile is only rewritten if any entry changed. Lines
+/// that aren't valid UTF-8 or JSON are copied through byte for byte.
+fn migrate(path: &Path, dry_run: bool) -> Result<Migration> {
+    let mut migration = Migration::default();
+    let mut content = Vec::new();
+
+    for line in archive::open(path)?.split(b'\n') {
+        let line = line?;
+
+        match serde_json::from_slice::<Value>(&line) {
+            _ if line.trim_ascii().is_empty() => content.extend_from_slice(&line),
+            Ok(entry) if schema::version(&entry) < SCHEMA_VERSION => {
+                serde_json::to_writer(&mut content, &schema::migrate(entry)?)?;
+                migration.migrated += 1;
+            }
+            Ok(entry) if schema::version(&entry) == SCHEMA_VERSION => {
+                content.extend_from_slice(&line)
+            }
+            _ => {
+                migration.unreadable += 1;
+                content.extend_from_slice(&line);
+            }
+        }
+        content.push(b'\n');
+    }
+
+    if migration.migrated > 0 && !dry_run {
+        archive::rewrite(path, &content)?;
+    }
+
+    Ok(migration)
+}
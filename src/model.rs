@@ -1,18 +1,24 @@
 use std::{
     fmt::Display,
-    fs::File,
-    io::{BufRead, BufReader},
+    io::BufRead,
+    net::{IpAddr, SocketAddr},
     path::{Path, PathBuf},
+    str::FromStr,
     time::Duration,
 };
 
-use chrono::{DateTime, TimeDelta, Utc};
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::{
-    time::{Humanize, timespan_string, timespan_string_custom},
+    archive,
+    config::DebounceConfig,
+    database::Database,
+    schema,
+    time::{Humanize, period_duration, timespan_between, timespan_string, timespan_string_custom},
     tracker::DowntimeTracker,
 };
 
@@ -20,6 +26,8 @@ use crate::{
 pub struct Report {
     items: Vec<ReportItem>,
     log_precision: Option<OutageLogPrecision>,
+    debounce: DebounceConfig,
+    gaps: Vec<MonitorGap>,
 }
 
 impl Report {
@@ -32,7 +40,66 @@ impl Report {
         Self {
             items,
             log_precision,
+            debounce: DebounceConfig::default(),
+            gaps: vec![],
         }
+        .with_gap_threshold(None)
+    }
+
+    /// Loads the given local days from the database, one report item per day.
+    pub fn from_database(
+        database: &Database,
+        days: &[NaiveDate],
+        log_precision: Option<OutageLogPrecision>,
+    ) -> Result<Self> {
+        let items = days
+            .iter()
+            .map(|day| {
+                let entries = database.entries_of_day(*day)?.into_iter().collect();
+                let logfile = Logfile::new(day.to_string(), database.path().to_path_buf());
+
+                Ok(ReportItem::from_entries(logfile, entries))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            items,
+            log_precision,
+            debounce: DebounceConfig::default(),
+            gaps: vec![],
+        }
+        .with_gap_threshold(None))
+    }
+
+    /// Applies the debounce rules of the monitor when outages are detected.
+    pub fn with_debounce(mut self, debounce: DebounceConfig) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    /// Detects the periods in which the monitor was not running. Without a threshold, a period
+    /// counts once the log is silent for three heartbeats, as long as the monitor wrote any.
+    pub fn with_gap_threshold(mut self, gap_threshold: Option<u64>) -> Self {
+        let log_precision = self.log_precision();
+
+        for item in self.items.iter_mut() {
+            item.gaps = MonitorGap::find(
+                item.results.iter(),
+                item.heartbeats.iter(),
+                item.clock_jumps.iter(),
+                gap_threshold,
+                log_precision,
+            );
+        }
+        self.gaps = MonitorGap::find(
+            self.iter_all_results(),
+            self.items.iter().flat_map(|item| &item.heartbeats),
+            self.items.iter().flat_map(|item| &item.clock_jumps),
+            gap_threshold,
+            log_precision,
+        );
+
+        self
     }
 
     pub fn iter_items(&self) -> impl Iterator<Item = &ReportItem> {
@@ -48,12 +115,29 @@ impl Report {
     }
 
     pub fn all_outages<'a>(&'a self) -> Vec<Outage<'a>> {
-        let mut tracker = DowntimeTracker::new();
+        self.all_periods(
+            DowntimeTracker::for_outages(&self.debounce).with_gaps(self.gaps.clone()),
+            OutageKind::Offline,
+        )
+    }
+
+    pub fn all_slow_periods<'a>(&'a self, latency_threshold: Option<u64>) -> Vec<Outage<'a>> {
+        self.all_periods(
+            DowntimeTracker::for_slowness(latency_threshold).with_gaps(self.gaps.clone()),
+            OutageKind::Slow,
+        )
+    }
 
+    fn all_periods<'a>(
+        &'a self,
+        mut tracker: DowntimeTracker<'a>,
+        kind: OutageKind,
+    ) -> Vec<Outage<'a>> {
         self.iter_all_results()
             .filter_map(|result| {
                 tracker.track(result, |start, end| {
                     Some(Outage::from_start_end(
+                        kind,
                         start,
                         end,
                         self.log_precision.unwrap_or(OutageLogPrecision::Normal),
@@ -66,36 +150,211 @@ impl Report {
     pub fn log_precision(&self) -> OutageLogPrecision {
         self.log_precision.unwrap_or(OutageLogPrecision::Normal)
     }
+
+    pub fn debounce(&self) -> &DebounceConfig {
+        &self.debounce
+    }
+
+    pub fn stats(&self) -> CheckStats {
+        CheckStats::from_results(self.iter_all_results(), &self.gaps)
+    }
+
+    /// Number of entries that could not be read, across all logfiles.
+    pub fn unreadable(&self) -> usize {
+        self.iter_items().map(|item| item.unreadable).sum()
+    }
+
+    /// Periods in which the monitor was not running, across all logfiles.
+    pub fn gaps(&self) -> &[MonitorGap] {
+        &self.gaps
+    }
 }
 
 impl Report {
-    fn collect_results_from_logfile(logfile: &Logfile) -> Vec<InternetCheckResult> {
-        Self::collect_results_from_path(&logfile.path)
+    fn collect_entries_from_logfile(logfile: &Logfile) -> LogEntries {
+        Self::collect_entries_from_path(&logfile.path)
     }
 
-    fn collect_results_from_path<P: AsRef<Path>>(path: P) -> Vec<InternetCheckResult> {
-        match File::open(path.as_ref()) {
-            Ok(file) => Self::collect_results_from_file(file),
-            Err(_) => vec![],
+    fn collect_entries_from_path<P: AsRef<Path>>(path: P) -> LogEntries {
+        match archive::open(path.as_ref()) {
+            Ok(reader) => Self::collect_entries_from_reader(reader),
+            Err(_) => LogEntries::default(),
         }
     }
 
-    fn collect_results_from_file(file: File) -> Vec<InternetCheckResult> {
-        let reader = BufReader::new(file);
-        Self::collect_results_from_reader(reader)
+    /// Skips lines that are no valid UTF-8 or JSON. Only an I/O error ends the reading, as it would
+    /// repeat for every following line.
+    fn collect_entries_from_reader(reader: impl BufRead) -> LogEntries {
+        reader
+            .split(b'\n')
+            .map_while(|l| l.ok())
+            .filter_map(|line| serde_json::from_slice::<Value>(&line).ok())
+            .collect()
     }
+}
 
-    fn collect_results_from_reader(reader: BufReader<File>) -> Vec<InternetCheckResult> {
-        reader
-            .lines()
-            .filter_map(|l| l.ok())
-            .filter_map(|line| {
-                serde_json::from_str(&line)
-                    .ok()
-                    .and_then(|mut v: Value| v.get_mut("result").map(|r| r.take()))
-                    .and_then(|f| serde_json::from_value::<InternetCheckResult>(f).ok())
+#[derive(Default)]
+struct LogEntries {
+    results: Vec<InternetCheckResult>,
+    heartbeats: Vec<Heartbeat>,
+    clock_jumps: Vec<ClockJump>,
+    /// Entries of an unknown schema version and check results that failed to deserialize.
+    unreadable: usize,
+}
+
+impl FromIterator<Value> for LogEntries {
+    /// Collects the entries, which are migrated to the current schema version first.
+    fn from_iter<T: IntoIterator<Item = Value>>(values: T) -> Self {
+        let mut entries = LogEntries::default();
+
+        for value in values {
+            let Ok(mut value) = schema::migrate(value) else {
+                entries.unreadable += 1;
+                continue;
+            };
+
+            if let Some(result) = value.get_mut("result").map(|r| r.take()) {
+                match serde_json::from_value::<InternetCheckResult>(result) {
+                    Ok(result) => entries.results.push(result),
+                    Err(_) => entries.unreadable += 1,
+                }
+            } else if let Some(clock_jump) = value
+                .get_mut("clock_jump")
+                .map(|c| c.take())
+                .and_then(|c| serde_json::from_value::<ClockJump>(c).ok())
+            {
+                entries.clock_jumps.push(clock_jump);
+            } else if let Ok(heartbeat) = serde_json::from_value::<Heartbeat>(value) {
+                entries.heartbeats.push(heartbeat);
+            }
+        }
+
+        entries
+    }
+}
+
+/// Entry the monitor writes periodically, so the reports can tell when it was not running.
+#[derive(Deserialize, Debug, Clone)]
+pub struct Heartbeat {
+    timestamp: DateTime<Utc>,
+    /// Seconds until the next heartbeat.
+    heartbeat: u64,
+}
+
+/// Jump of the wall clock between two checks compared to the monotonic clock, which stops while
+/// the machine is suspended.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct ClockJump {
+    /// Last check before the jump.
+    pub since: DateTime<Utc>,
+    /// First check after the jump.
+    pub until: DateTime<Utc>,
+    /// Seconds the wall clock moved further than the monotonic clock.
+    pub drift: i64,
+}
+
+/// Period in which the monitor was not running, e.g. because it was stopped, killed or the
+/// machine was asleep. It starts at the last entry before and ends at the first entry after it.
+#[derive(Debug, Clone, Copy)]
+pub struct MonitorGap {
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    log_precision: OutageLogPrecision,
+}
+
+impl MonitorGap {
+    /// The monitor was not running after a graceful shutdown, between the checks around a
+    /// suspend or clock jump, and while it was silent for longer than the threshold. Without a
+    /// threshold, silence only counts once the monitor announced its heartbeat, and for longer
+    /// than three heartbeats.
+    fn find<'a>(
+        results: impl Iterator<Item = &'a InternetCheckResult>,
+        heartbeats: impl Iterator<Item = &'a Heartbeat>,
+        clock_jumps: impl Iterator<Item = &'a ClockJump>,
+        gap_threshold: Option<u64>,
+        log_precision: OutageLogPrecision,
+    ) -> Vec<MonitorGap> {
+        let mut entries = results
+            .map(|r| {
+                let stopped = matches!(r.cycle, InternetCheckCycle::Stopped);
+                (r.timestamp, stopped, None)
             })
-            .collect::<Vec<InternetCheckResult>>()
+            .chain(heartbeats.map(|h| (h.timestamp, false, Some(h.heartbeat))))
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|(timestamp, _, _)| *timestamp);
+
+        let mut heartbeat = None;
+        let mut gaps = vec![];
+
+        for pair in entries.windows(2) {
+            let ((start, stopped, beat), (end, _, _)) = (pair[0], pair[1]);
+            heartbeat = beat.filter(|b| *b > 0).or(heartbeat);
+
+            let span = period_duration(&start, &end);
+            let threshold = gap_threshold.or(heartbeat.map(|b| b * 3));
+            let silent = threshold.is_some_and(|t| span.num_seconds() > t as i64);
+
+            if (stopped && span > TimeDelta::zero()) || silent {
+                gaps.push(MonitorGap {
+                    start,
+                    end,
+                    log_precision,
+                });
+            }
+        }
+
+        gaps.extend(
+            clock_jumps
+                .filter(|jump| jump.until > jump.since)
+                .map(|jump| MonitorGap {
+                    start: jump.since,
+                    end: jump.until,
+                    log_precision,
+                }),
+        );
+
+        Self::merge(gaps)
+    }
+
+    /// Merges overlapping gaps, e.g. a suspend that was also detected by the missing heartbeats.
+    fn merge(mut gaps: Vec<MonitorGap>) -> Vec<MonitorGap> {
+        gaps.sort_by_key(|gap| gap.start);
+
+        let mut merged: Vec<MonitorGap> = Vec::with_capacity(gaps.len());
+        for gap in gaps {
+            match merged.last_mut() {
+                Some(last) if gap.start <= last.end => last.end = last.end.max(gap.end),
+                _ => merged.push(gap),
+            }
+        }
+
+        merged
+    }
+
+    pub fn duration(&self) -> TimeDelta {
+        period_duration(&self.start, &self.end)
+    }
+
+    /// Share of the given span that overlaps with the gap.
+    pub fn overlap(&self, start: &DateTime<Utc>, end: &DateTime<Utc>) -> TimeDelta {
+        let overlap = period_duration(&self.start.max(*start), &self.end.min(*end));
+        overlap.max(TimeDelta::zero())
+    }
+}
+
+impl Display for MonitorGap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let format_time = match self.log_precision {
+            OutageLogPrecision::Normal => None,
+            OutageLogPrecision::Exact => Some("%H:%M:%S"),
+        };
+
+        write!(
+            f,
+            "Not running at {} for {}",
+            timespan_between(&self.start, &self.end, None, format_time),
+            self.duration().humanize(),
+        )
     }
 }
 
@@ -103,17 +362,56 @@ impl Report {
 pub struct ReportItem {
     logfile: Logfile,
     results: Vec<InternetCheckResult>,
+    heartbeats: Vec<Heartbeat>,
+    clock_jumps: Vec<ClockJump>,
+    gaps: Vec<MonitorGap>,
+    unreadable: usize,
 }
 
 impl<'a> ReportItem {
-    pub fn outages(&'a self, log_precision: OutageLogPrecision) -> Vec<Outage<'a>> {
-        let mut tracker = DowntimeTracker::new();
+    pub fn outages(
+        &'a self,
+        debounce: &DebounceConfig,
+        log_precision: OutageLogPrecision,
+    ) -> Vec<Outage<'a>> {
+        self.periods(
+            DowntimeTracker::for_outages(debounce).with_gaps(self.gaps.clone()),
+            OutageKind::Offline,
+            log_precision,
+        )
+    }
+
+    pub fn degradations(&'a self, log_precision: OutageLogPrecision) -> Vec<Outage<'a>> {
+        self.periods(
+            DowntimeTracker::for_state(Connectivity::Degraded).with_gaps(self.gaps.clone()),
+            OutageKind::Degraded,
+            log_precision,
+        )
+    }
+
+    pub fn slow_periods(
+        &'a self,
+        latency_threshold: Option<u64>,
+        log_precision: OutageLogPrecision,
+    ) -> Vec<Outage<'a>> {
+        self.periods(
+            DowntimeTracker::for_slowness(latency_threshold).with_gaps(self.gaps.clone()),
+            OutageKind::Slow,
+            log_precision,
+        )
+    }
 
+    fn periods(
+        &'a self,
+        mut tracker: DowntimeTracker<'a>,
+        kind: OutageKind,
+        log_precision: OutageLogPrecision,
+    ) -> Vec<Outage<'a>> {
         self.results
             .iter()
             .filter_map(|result| {
                 tracker.track(result, |start, end| {
-                    Some(Outage::from_start_end(start, end, log_precision))
+                    Some(Outage::from_start_end(kind, start, end, log_precision))
                 })
             })
             .collect()
@@ -126,13 +424,38 @@ impl<'a> ReportItem {
     pub fn iter_results(&self) -> impl Iterator<Item = &InternetCheckResult> {
         self.results.iter()
     }
+
+    pub fn stats(&self) -> CheckStats {
+        CheckStats::from_results(self.iter_results(), &self.gaps)
+    }
+
+    pub fn gaps(&self) -> &[MonitorGap] {
+        &self.gaps
+    }
 }
 
 impl ReportItem {
     fn from_logfile(logfile: Logfile) -> Self {
-        let results = Report::collect_results_from_logfile(&logfile);
+        let entries = Report::collect_entries_from_logfile(&logfile);
+        Self::from_entries(logfile, entries)
+    }
+
+    fn from_entries(logfile: Logfile, entries: LogEntries) -> Self {
+        let LogEntries {
+            results,
+            heartbeats,
+            clock_jumps,
+            unreadable,
+        } = entries;
 
-        ReportItem { logfile, results }
+        ReportItem {
+            logfile,
+            results,
+            heartbeats,
+            clock_jumps,
+            gaps: vec![],
+            unreadable,
+        }
     }
 }
 
@@ -157,7 +480,123 @@ impl Logfile {
     }
 }
 
+/// Uptime and latency of the logged checks. Every check counts until the next one, so logs that
+/// only contain the transitions are rated correctly as well, they just provide fewer latencies.
+/// Periods in which the monitor was not running are counted separately.
+#[derive(Debug, Clone, Default)]
+pub struct CheckStats {
+    checks: usize,
+    online: TimeDelta,
+    degraded: TimeDelta,
+    offline: TimeDelta,
+    not_running: TimeDelta,
+    /// Average latencies of the checks that reached the internet, sorted ascending.
+    latencies: Vec<Duration>,
+}
+
+impl CheckStats {
+    fn from_results<'a>(
+        results: impl Iterator<Item = &'a InternetCheckResult>,
+        gaps: &[MonitorGap],
+    ) -> Self {
+        let mut stats = Self::default();
+        let mut results = results.peekable();
+
+        while let Some(result) = results.next() {
+            stats.checks += 1;
+
+            if result.connectivity != Connectivity::Offline {
+                stats.latencies.push(result.avg);
+            }
+
+            let Some(next) = results.peek() else {
+                continue;
+            };
+            let not_running = gaps
+                .iter()
+                .map(|gap| gap.overlap(&result.timestamp, &next.timestamp))
+                .sum::<TimeDelta>();
+            let span = period_duration(&result.timestamp, &next.timestamp) - not_running;
+            stats.not_running += not_running;
+            if span <= TimeDelta::zero() {
+                continue;
+            }
+
+            match result.connectivity {
+                Connectivity::Online => stats.online += span,
+                Connectivity::Degraded => stats.degraded += span,
+                Connectivity::Offline => stats.offline += span,
+            }
+        }
+        stats.latencies.sort();
+
+        stats
+    }
+
+    pub fn checks(&self) -> usize {
+        self.checks
+    }
+
+    pub fn observed(&self) -> TimeDelta {
+        self.online + self.degraded + self.offline
+    }
+
+    pub fn degraded(&self) -> TimeDelta {
+        self.degraded
+    }
+
+    pub fn offline(&self) -> TimeDelta {
+        self.offline
+    }
+
+    pub fn not_running(&self) -> TimeDelta {
+        self.not_running
+    }
+
+    /// Share of the observed time the internet was reachable, degraded or not.
+    pub fn uptime(&self) -> Option<f64> {
+        Self::share(self.online + self.degraded, self.observed())
+    }
+
+    pub fn degraded_share(&self) -> Option<f64> {
+        Self::share(self.degraded, self.observed())
+    }
+
+    pub fn latency_samples(&self) -> usize {
+        self.latencies.len()
+    }
+
+    pub fn latency_avg(&self) -> Option<Duration> {
+        let count = u32::try_from(self.latencies.len())
+            .ok()
+            .filter(|c| *c > 0)?;
+        Some(self.latencies.iter().sum::<Duration>() / count)
+    }
+
+    pub fn latency_min(&self) -> Option<Duration> {
+        self.latencies.first().copied()
+    }
+
+    pub fn latency_max(&self) -> Option<Duration> {
+        self.latencies.last().copied()
+    }
+
+    /// Nearest-rank percentile (0.0 - 1.0) of the latencies.
+    pub fn latency_percentile(&self, percentile: f64) -> Option<Duration> {
+        let rank = (percentile * self.latencies.len() as f64).ceil() as usize;
+        self.latencies.get(rank.saturating_sub(1)).copied()
+    }
+
+    fn share(part: TimeDelta, total: TimeDelta) -> Option<f64> {
+        match total.num_milliseconds() {
+            0 => None,
+            total => Some(part.num_milliseconds() as f64 / total as f64),
+        }
+    }
+}
+
 pub struct Outage<'a> {
+    kind: OutageKind,
     start: &'a InternetCheckResult,
     end: &'a InternetCheckResult,
     duration: TimeDelta,
@@ -168,16 +607,31 @@ impl<'a> Outage<'a> {
     pub fn duration(&'a self) -> &'a TimeDelta {
         &self.duration
     }
+
+    pub fn start(&self) -> &DateTime<Utc> {
+        &self.start.timestamp
+    }
+
+    pub fn end(&self) -> &DateTime<Utc> {
+        &self.end.timestamp
+    }
+
+    /// Target results that failed at the start of the outage.
+    pub fn iter_failed_targets(&self) -> impl Iterator<Item = &'a TargetResult> {
+        self.start.iter_failed_targets()
+    }
 }
 
 impl<'a> Outage<'a> {
     fn new(
+        kind: OutageKind,
         start: &'a InternetCheckResult,
         end: &'a InternetCheckResult,
         duration: TimeDelta,
         log_precision: OutageLogPrecision,
     ) -> Self {
         Self {
+            kind,
             start,
             end,
             duration,
@@ -186,12 +640,13 @@ impl<'a> Outage<'a> {
     }
 
     fn from_start_end(
+        kind: OutageKind,
         start: &'a InternetCheckResult,
         end: &'a InternetCheckResult,
         log_precision: OutageLogPrecision,
     ) -> Self {
-        let duration = end.timestamp - start.timestamp;
-        Self::new(start, end, duration, log_precision)
+        let duration = period_duration(&start.timestamp, &end.timestamp);
+        Self::new(kind, start, end, duration, log_precision)
     }
 }
 
@@ -204,7 +659,30 @@ impl<'a> Display for Outage<'a> {
             }
         };
 
-        write!(f, "Outage at {} for {}", timespan, self.duration.humanize(),)
+        write!(
+            f,
+            "{} at {} for {}",
+            self.kind,
+            timespan,
+            self.duration.humanize(),
+        )
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub enum OutageKind {
+    Offline,
+    Degraded,
+    Slow,
+}
+
+impl Display for OutageKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutageKind::Offline => write!(f, "Outage"),
+            OutageKind::Degraded => write!(f, "Degraded"),
+            OutageKind::Slow => write!(f, "Slow"),
+        }
     }
 }
 
@@ -215,10 +693,246 @@ pub enum OutageLogPrecision {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
-pub enum CheckTarget {
-    Google,
-    Example,
-    IP,
+#[serde(try_from = "TargetConfig")]
+pub struct CheckTarget {
+    name: String,
+    #[serde(flatten)]
+    probe: Probe,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timeout: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    latency_threshold: Option<u64>,
+}
+
+impl CheckTarget {
+    pub fn new<N: Into<String>>(name: N, probe: Probe) -> Self {
+        Self {
+            name: name.into(),
+            probe,
+            timeout: None,
+            latency_threshold: None,
+        }
+    }
+
+    pub fn http<N: Into<String>, U: Into<String>>(name: N, url: U) -> Self {
+        Self::new(
+            name,
+            Probe::Http {
+                url: url.into(),
+                expected_status: None,
+            },
+        )
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn probe(&self) -> &Probe {
+        &self.probe
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout.map(Duration::from_secs)
+    }
+
+    pub fn latency_threshold(&self) -> Option<u64> {
+        self.latency_threshold
+    }
+}
+
+/// Target as written in the config file. The fields of all probes are checked against each other
+/// here, since the flattened probe would otherwise ignore unknown or misplaced fields.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TargetConfig {
+    name: String,
+    url: Option<String>,
+    expected_status: Option<u16>,
+    address: Option<String>,
+    domain: Option<String>,
+    resolver: Option<IpAddr>,
+    timeout: Option<u64>,
+    latency_threshold: Option<u64>,
+}
+
+impl TryFrom<TargetConfig> for CheckTarget {
+    type Error = String;
+
+    fn try_from(config: TargetConfig) -> Result<Self, Self::Error> {
+        let name = config.name;
+        let probe = match (config.url, config.address, config.domain) {
+            (Some(url), None, None) => Probe::Http {
+                url,
+                expected_status: config.expected_status,
+            },
+            (None, Some(address), None) => {
+                check_address(&address).map_err(|err| format!("Target '{name}': {err}"))?;
+                Probe::Tcp { address }
+            }
+            (None, None, Some(domain)) => Probe::Dns {
+                domain,
+                resolver: config.resolver,
+            },
+            _ => {
+                return Err(format!(
+                    "Target '{name}' needs exactly one of 'url', 'address' or 'domain'"
+                ));
+            }
+        };
+
+        match &probe {
+            Probe::Tcp { .. } | Probe::Dns { .. } if config.expected_status.is_some() => {
+                return Err(format!(
+                    "Target '{name}' sets 'expected_status', which only HTTP targets support"
+                ));
+            }
+            Probe::Http { .. } | Probe::Tcp { .. } if config.resolver.is_some() => {
+                return Err(format!(
+                    "Target '{name}' sets 'resolver', which only DNS targets support"
+                ));
+            }
+            _ => {}
+        }
+
+        Ok(Self {
+            name,
+            probe,
+            timeout: config.timeout,
+            latency_threshold: config.latency_threshold,
+        })
+    }
+}
+
+impl FromStr for CheckTarget {
+    type Err = anyhow::Error;
+
+    /// Parses targets in the form `NAME=URL[,OPTION=VALUE...]`. URLs starting with `tcp://` or
+    /// `dns://` create TCP connect or DNS lookup probes, every other URL an HTTP probe.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(',');
+        let (name, url) = parts
+            .next()
+            .and_then(|p| p.split_once('='))
+            .map(|(name, url)| (name.trim(), url.trim()))
+            .filter(|(name, url)| !name.is_empty() && !url.is_empty())
+            .ok_or(anyhow::anyhow!(
+                "Invalid target '{s}', expected NAME=URL[,status=CODE][,timeout=SECS][,latency=MS][,resolver=IP]"
+            ))?;
+        let probe = if let Some(address) = url.strip_prefix("tcp://") {
+            check_address(address)
+                .map_err(|err| anyhow::anyhow!("Invalid target '{name}': {err}"))?;
+            Probe::Tcp {
+                address: address.into(),
+            }
+        } else if let Some(domain) = url.strip_prefix("dns://") {
+            Probe::Dns {
+                domain: domain.into(),
+                resolver: None,
+            }
+        } else {
+            Probe::Http {
+                url: url.into(),
+                expected_status: None,
+            }
+        };
+        let mut target = CheckTarget::new(name, probe);
+
+        for option in parts {
+            let parsed = option.split_once('=').map(|(k, v)| (k.trim(), v.trim()));
+
+            match (parsed, &mut target.probe) {
+                (Some(("timeout", value)), _) => {
+                    target.timeout = Some(value.parse().map_err(|_| {
+                        anyhow::anyhow!("Invalid timeout '{value}' for target '{name}'")
+                    })?)
+                }
+                (Some(("latency", value)), _) => {
+                    target.latency_threshold = Some(value.parse().map_err(|_| {
+                        anyhow::anyhow!("Invalid latency threshold '{value}' for target '{name}'")
+                    })?)
+                }
+                (
+                    Some(("status", value)),
+                    Probe::Http {
+                        expected_status, ..
+                    },
+                ) => {
+                    *expected_status = Some(value.parse().map_err(|_| {
+                        anyhow::anyhow!("Invalid status '{value}' for target '{name}'")
+                    })?)
+                }
+                (Some(("resolver", value)), Probe::Dns { resolver, .. }) => {
+                    *resolver = Some(value.parse().map_err(|_| {
+                        anyhow::anyhow!("Invalid resolver '{value}' for target '{name}'")
+                    })?)
+                }
+                _ => {
+                    return Err(anyhow::anyhow!(
+                        "Unknown target option '{option}' for '{name}'"
+                    ));
+                }
+            }
+        }
+
+        Ok(target)
+    }
+}
+
+/// Checks that the address of a TCP target has a host and a port, like `example.com:443` or
+/// `[::1]:22`. The host itself is only resolved when it's checked.
+fn check_address(address: &str) -> Result<(), String> {
+    match address.rsplit_once(':') {
+        Some((host, port)) if !host.is_empty() && port.parse::<u16>().is_ok_and(|p| p > 0) => {
+            Ok(())
+        }
+        _ => Err(format!("invalid address '{address}', expected HOST:PORT")),
+    }
+}
+
+/// Describes how a target is checked. The variant is picked by the fields given in the config
+/// file: `url` for HTTP, `address` for TCP and `domain` for DNS probes.
+#[derive(Serialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum Probe {
+    Http {
+        url: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        expected_status: Option<u16>,
+    },
+    Tcp {
+        address: String,
+    },
+    Dns {
+        domain: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        resolver: Option<IpAddr>,
+    },
+}
+
+/// Probe specific part of a [`TargetResult`]. HTTP results keep their status code in
+/// `TargetResult::status_code`, because logs written before TCP and DNS probes existed only
+/// contain HTTP results.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub enum ProbeResult {
+    #[default]
+    Http,
+    Tcp {
+        peer: Option<SocketAddr>,
+    },
+    Dns {
+        addresses: Vec<IpAddr>,
+    },
+}
+
+impl Display for ProbeResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProbeResult::Http => write!(f, "http"),
+            ProbeResult::Tcp { .. } => write!(f, "tcp"),
+            ProbeResult::Dns { .. } => write!(f, "dns"),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -230,28 +944,68 @@ pub enum CheckError {
     HttpStatus(u16),
     Other(String),
     InvalidRequest,
+    Unreachable,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+impl Display for CheckError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CheckError::Timeout => write!(f, "timeout"),
+            CheckError::DnsFailure => write!(f, "dns failure"),
+            CheckError::ConnectionRefused => write!(f, "connection refused"),
+            CheckError::TlsError => write!(f, "tls error"),
+            CheckError::HttpStatus(status) => write!(f, "http status {status}"),
+            CheckError::Other(msg) => write!(f, "{msg}"),
+            CheckError::InvalidRequest => write!(f, "invalid request"),
+            CheckError::Unreachable => write!(f, "unreachable"),
+        }
+    }
+}
+
+impl CheckError {
+    /// Short name of the error kind, without any details.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            CheckError::Timeout => "timeout",
+            CheckError::DnsFailure => "dns_failure",
+            CheckError::ConnectionRefused => "connection_refused",
+            CheckError::TlsError => "tls_error",
+            CheckError::HttpStatus(_) => "http_status",
+            CheckError::Other(_) => "other",
+            CheckError::InvalidRequest => "invalid_request",
+            CheckError::Unreachable => "unreachable",
+        }
+    }
+}
+
+/// Latency threshold in milliseconds, used if neither the monitor run nor the target set one.
+pub const DEFAULT_LATENCY_THRESHOLD: u64 = 500;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub enum LatencySpeed {
     Slow,
     Ok,
 }
 
 impl LatencySpeed {
-    pub fn new(results: &[&TargetResult], latency_threshold: Option<usize>) -> LatencySpeed {
-        if results.is_empty() {
+    /// Rates the average latency of the successful targets. Failed targets are left out, since a
+    /// timeout would rate the connection slow.
+    pub fn new(results: &[&TargetResult], latency_threshold: u64) -> LatencySpeed {
+        let succeeded = results.iter().filter(|r| r.success()).collect::<Vec<_>>();
+        if succeeded.is_empty() {
             return LatencySpeed::Ok;
         }
 
-        let threshold = latency_threshold.unwrap_or(500);
-
-        let sum: usize = results
+        let sum: u128 = succeeded
             .iter()
-            .map(|r| r.latency.get_duration().as_millis() as usize)
+            .map(|r| r.latency.get_duration().as_millis())
             .sum();
 
-        if (sum / results.len()) > threshold {
+        Self::from_millis(sum / succeeded.len() as u128, latency_threshold)
+    }
+
+    fn from_millis(millis: u128, latency_threshold: u64) -> LatencySpeed {
+        if millis > latency_threshold as u128 {
             LatencySpeed::Slow
         } else {
             LatencySpeed::Ok
@@ -263,19 +1017,30 @@ impl LatencySpeed {
 pub struct Latency {
     duration: Duration,
     speed: LatencySpeed,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    threshold: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    phases: Option<LatencyPhases>,
 }
 
 impl Latency {
-    pub fn from_duration(duration: Duration, threshold: Option<u128>) -> Latency {
-        let treshold_value = threshold.unwrap_or(500);
+    pub fn from_duration(duration: Duration, threshold: u64) -> Latency {
+        Latency {
+            duration,
+            speed: LatencySpeed::from_millis(duration.as_millis(), threshold),
+            threshold: Some(threshold),
+            phases: None,
+        }
+    }
 
-        let speed = if duration.as_millis() > treshold_value {
-            LatencySpeed::Slow
-        } else {
-            LatencySpeed::Ok
-        };
+    pub fn with_phases(mut self, phases: LatencyPhases) -> Self {
+        self.phases = Some(phases);
+
+        self
+    }
 
-        Latency { duration, speed }
+    pub fn phases(&self) -> Option<&LatencyPhases> {
+        self.phases.as_ref()
     }
 
     fn get_duration(&self) -> &Duration {
@@ -283,9 +1048,25 @@ impl Latency {
     }
 }
 
+/// Timings of the single steps of a probe. Phases a probe does not perform, or did not reach
+/// because an earlier phase failed, stay empty.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct LatencyPhases {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dns: Option<Duration>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connect: Option<Duration>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tls: Option<Duration>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_byte: Option<Duration>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct TargetResult {
-    target: CheckTarget,
+    target: String,
+    #[serde(default)]
+    probe: ProbeResult,
     success: bool,
     latency: Latency,
     status_code: Option<u16>,
@@ -294,7 +1075,8 @@ pub struct TargetResult {
 
 impl TargetResult {
     pub fn new(
-        target: CheckTarget,
+        target: String,
+        probe: ProbeResult,
         success: bool,
         latency: Latency,
         status_code: Option<u16>,
@@ -302,6 +1084,7 @@ impl TargetResult {
     ) -> TargetResult {
         TargetResult {
             target,
+            probe,
             success,
             latency,
             status_code,
@@ -309,10 +1092,26 @@ impl TargetResult {
         }
     }
 
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    pub fn probe(&self) -> &ProbeResult {
+        &self.probe
+    }
+
     pub fn success(&self) -> bool {
         self.success
     }
 
+    pub fn error(&self) -> Option<&CheckError> {
+        self.error.as_ref()
+    }
+
+    pub fn latency(&self) -> &Latency {
+        &self.latency
+    }
+
     pub fn latency_duration(&self) -> &Duration {
         &self.latency.duration
     }
@@ -321,23 +1120,15 @@ impl TargetResult {
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Connectivity {
     Online,
+    Degraded,
     Offline,
 }
 
-impl From<bool> for Connectivity {
-    fn from(value: bool) -> Self {
-        if value {
-            Connectivity::Online
-        } else {
-            Connectivity::Offline
-        }
-    }
-}
-
 impl Display for Connectivity {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Connectivity::Online => write!(f, "Online"),
+            Connectivity::Degraded => write!(f, "Degraded"),
             Connectivity::Offline => write!(f, "Offline"),
         }
     }
@@ -348,6 +1139,8 @@ pub struct InternetCheckResult {
     pub timestamp: DateTime<Utc>,
     connectivity: Connectivity,
     speed: LatencySpeed,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    latency_threshold: Option<u64>,
     results: Vec<TargetResult>,
     avg: Duration,
     cycle: InternetCheckCycle,
@@ -357,6 +1150,7 @@ impl InternetCheckResult {
     pub fn new(
         connectivity: Connectivity,
         speed: LatencySpeed,
+        latency_threshold: u64,
         results: Vec<TargetResult>,
         avg: Duration,
         cycle: InternetCheckCycle,
@@ -365,6 +1159,7 @@ impl InternetCheckResult {
             timestamp: Utc::now(),
             connectivity,
             speed,
+            latency_threshold: Some(latency_threshold),
             results,
             avg,
             cycle,
@@ -375,6 +1170,40 @@ impl InternetCheckResult {
         self.connectivity
     }
 
+    /// Re-evaluates the speed with the given threshold from the successful targets. Without a
+    /// threshold, the speed rated at the time of the check is used.
+    pub fn is_slow(&self, latency_threshold: Option<u64>) -> bool {
+        match latency_threshold {
+            Some(threshold) if self.results.iter().any(|r| r.success()) => {
+                let results = self.results.iter().collect::<Vec<_>>();
+
+                LatencySpeed::new(&results, threshold) == LatencySpeed::Slow
+            }
+            // Samples only keep the failed targets, but their average is of the successful ones.
+            Some(threshold) if self.connectivity != Connectivity::Offline => {
+                LatencySpeed::from_millis(self.avg.as_millis(), threshold) == LatencySpeed::Slow
+            }
+            Some(_) => false,
+            None => self.speed == LatencySpeed::Slow,
+        }
+    }
+
+    pub fn iter_target_results(&self) -> impl Iterator<Item = &TargetResult> {
+        self.results.iter()
+    }
+
+    pub fn iter_failed_targets(&self) -> impl Iterator<Item = &TargetResult> {
+        self.results.iter().filter(|r| !r.success())
+    }
+
+    /// Compact copy of the check for logging every cycle, that only keeps the failed targets.
+    pub fn sample(&self) -> InternetCheckResult {
+        InternetCheckResult {
+            results: self.iter_failed_targets().cloned().collect(),
+            ..self.clone()
+        }
+    }
+
     pub fn get_time(&self) -> String {
         self.timestamp.format("%d.%m.%y - %H:%M").to_string()
     }
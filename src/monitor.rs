@@ -1,52 +1,120 @@
-use std::{ops::Deref, sync::Arc, time::Duration};
+use std::{
+    path::PathBuf,
+    sync::{Arc, PoisonError, RwLock},
+    time::{Duration, Instant},
+};
 
 use anyhow::Result;
-use reqwest::Client;
+use chrono::{DateTime, Local, TimeDelta, Utc};
+use serde::Serialize;
+use tokio::task::JoinHandle;
 
 use crate::{
-    app::monitor::MonitorArgs,
+    app::monitor::{MonitorArgs, RecordMode},
     check::check_connection,
-    log::Logger,
-    model::{Connectivity, InternetCheckCycle},
+    config::{Config, MonitorConfig},
+    dashboard::Dashboard,
+    log::{Logger, LoggerBuilder},
+    model::{ClockJump, Connectivity, InternetCheckCycle, InternetCheckResult},
+    notify::{Notifier, Transition, TransitionEvent},
     project::Project,
+    prune::{PruneOutcome, prune},
     runner::run_loop,
+    server,
+    status::MonitorStatus,
+    systemd::NotifySocket,
+    tracker::{DebounceStep, Debouncer},
 };
 
 pub const DEFAULT_MONITOR_INTERVAL: u64 = 5;
 pub const DEFAULT_MONITOR_TIMEOUT: u64 = 3;
 pub const DEFAULT_MONITOR_EXCLUDE_STOPPED: bool = false;
+pub const DEFAULT_MONITOR_HEARTBEAT: u64 = 60;
 
-pub async fn run(args: MonitorArgs, project: Project) -> Result<()> {
-    let log_dir = match &args.logger.dir {
-        Some(path) => path.deref(),
-        None => project.log_dir(),
-    };
+/// Seconds between two runs of the retention limits.
+const RETENTION_INTERVAL: u64 = 3600;
+
+/// Seconds the wall clock may drift from the monotonic clock between two checks, before it
+/// counts as a suspend or clock jump.
+const CLOCK_JUMP_TOLERANCE: i64 = 5;
 
-    let logger = Logger::builder()
-        .with_mode(args.logger.mode)
-        .with_dir(log_dir)
-        .with_file_prefix(args.logger.filename)
-        .with_max_size(args.logger.size)
-        .build()?;
-    let logger = Arc::new(logger);
+pub async fn run(args: MonitorArgs, config: Config, project: Project) -> Result<()> {
+    let config = args.clone().merge(config);
+    config.check_targets()?;
+    let tui = config.monitor.tui;
+    let logger = Arc::new(logger_builder(&config, &project, tui).build()?);
+    let notifier = Arc::new(Notifier::new(Arc::clone(&logger), &config));
+    let status = Arc::new(MonitorStatus::default());
+
+    if let Some(listen) = config.metrics.listen {
+        server::bind_metrics(listen)
+            .await?
+            .spawn(Arc::clone(&status), Arc::clone(&logger));
+    }
+    if let Some(listen) = config.status.listen {
+        server::bind_status(listen)
+            .await?
+            .spawn(Arc::clone(&status), Arc::clone(&logger));
+    }
+    if let Some(socket) = &config.status.socket {
+        server::bind_status_socket(socket)?.spawn(Arc::clone(&status), Arc::clone(&logger));
+    }
+    let _dashboard = match tui {
+        true => Some(Dashboard::start(Arc::clone(&status))?),
+        false => None,
+    };
 
-    let client = Client::builder()
-        .timeout(Duration::from_secs(args.observer.timeout))
-        .build()?;
+    let heartbeat = match config.monitor.heartbeat {
+        0 => None,
+        interval => Some(spawn_heartbeat(Arc::clone(&logger), interval)),
+    };
+    let retention = match config.retention.is_enabled() {
+        true => Some(spawn_retention(
+            Arc::clone(&logger),
+            config.clone(),
+            config.log_dir(&project).to_path_buf(),
+        )),
+        false => None,
+    };
+    let config = SharedConfig::new(config);
+    let notify_socket = NotifySocket::from_env();
 
     run_loop(
-        client.clone(),
         Arc::clone(&logger),
-        Duration::from_secs(args.observer.interval),
-        observe_connection,
+        |state: &ObservedState| state.schedule.duration(),
+        |logger, previous| {
+            observe_connection(
+                logger,
+                config.current(),
+                Arc::clone(&notifier),
+                Arc::clone(&status),
+                &notify_socket,
+                previous,
+            )
+        },
         Some(async || {
-            if args.observer.exclude_stopped {
+            if let Some(heartbeat) = &heartbeat {
+                heartbeat.abort();
+            }
+            if let Some(retention) = &retention {
+                retention.abort();
+            }
+            if let Err(err) = notify_socket.stopping() {
+                log!(
+                    logger,
+                    "Systemd notification failed",
+                    error = format!("{err:#}")
+                )?;
+            }
+
+            let config = config.current();
+            if config.monitor.exclude_stopped {
                 log!(
                     logger,
                     "Graceful shutdown, finally connection check skipped"
                 )?;
             } else {
-                let result = check_connection(client, None, InternetCheckCycle::Stopped).await;
+                let result = check(&config, InternetCheckCycle::Stopped).await;
                 log!(
                     logger,
                     "Graceful shutdown, perform final connection check",
@@ -56,36 +124,557 @@ pub async fn run(args: MonitorArgs, project: Project) -> Result<()> {
 
             Ok(())
         }),
+        Some(
+            async || match reload(&args, &config.current(), &project, &logger, tui) {
+                Ok(reloaded) => {
+                    let targets = reloaded
+                        .targets
+                        .iter()
+                        .map(|t| t.name().to_string())
+                        .collect::<Vec<_>>();
+                    let interval = reloaded.monitor.interval;
+                    let source = reloaded.source().map(|s| s.to_path_buf());
+
+                    config.replace(reloaded);
+                    log!(logger, "Config reloaded", source, targets, interval)
+                }
+                Err(err) => log!(logger, "Config reload failed", error = format!("{err:#}")),
+            },
+        ),
     )
     .await?;
 
     Ok(())
 }
 
+/// Reads the config file again and applies the command line flags on top, like on startup. The
+/// targets, intervals and log settings take effect with the next check, hooks, webhooks, servers,
+/// the debounce rules and the retention limits only after a restart.
+fn reload(
+    args: &MonitorArgs,
+    current: &Config,
+    project: &Project,
+    logger: &Logger,
+    tui: bool,
+) -> Result<Config> {
+    let config = args.clone().merge(Config::load(current.source(), project)?);
+    config.check_targets()?;
+    logger.reload(logger_builder(&config, project, tui))?;
+
+    Ok(config)
+}
+
+fn logger_builder(config: &Config, project: &Project, tui: bool) -> LoggerBuilder {
+    let log_mode = match tui {
+        true => config.log.mode.without_stdout(),
+        false => config.log.mode.clone(),
+    };
+
+    Logger::builder()
+        .with_mode(log_mode)
+        .with_console_format(config.log.console)
+        .with_socket(config.log.socket.clone())
+        .with_compression(config.log.compress)
+        .with_database(config.database(project))
+        .with_dir(config.log_dir(project))
+        .with_file_prefix(&config.log.filename)
+        .with_max_size(config.log.size)
+        .with_terminal(!tui)
+}
+
+/// Config of the running monitor, which is replaced when it is reloaded.
+struct SharedConfig(RwLock<Arc<Config>>);
+
+impl SharedConfig {
+    fn new(config: Config) -> Self {
+        Self(RwLock::new(Arc::new(config)))
+    }
+
+    fn current(&self) -> Arc<Config> {
+        let config = self.0.read().unwrap_or_else(PoisonError::into_inner);
+        Arc::clone(&config)
+    }
+
+    fn replace(&self, config: Config) {
+        *self.0.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(config);
+    }
+}
+
+/// Logs a heartbeat every interval, independent of the checks and whether they are logged.
+fn spawn_heartbeat(logger: Arc<Logger>, interval: u64) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticks = tokio::time::interval(Duration::from_secs(interval));
+
+        loop {
+            ticks.tick().await;
+            if log!(logger, "Monitor running", heartbeat = interval).is_err() {
+                break;
+            }
+        }
+    })
+}
+
+/// Removes the logfiles that exceed the retention limits on startup and every hour after.
+fn spawn_retention(logger: Arc<Logger>, config: Config, dir: PathBuf) -> JoinHandle<()> {
+    let config = Arc::new(config);
+
+    tokio::spawn(async move {
+        let mut ticks = tokio::time::interval(Duration::from_secs(RETENTION_INTERVAL));
+
+        loop {
+            ticks.tick().await;
+
+            let (config, dir) = (Arc::clone(&config), dir.clone());
+            let outcome = tokio::task::spawn_blocking(move || prune(&dir, &config, false))
+                .await
+                .map_err(anyhow::Error::from)
+                .and_then(|outcome| outcome);
+            let logged = match outcome {
+                Ok(outcome) => log_pruned(&logger, outcome),
+                Err(err) => log!(logger, "Logfile pruning failed", error = format!("{err:#}")),
+            };
+            if logged.is_err() {
+                break;
+            }
+        }
+    })
+}
+
+fn log_pruned(logger: &Logger, outcome: PruneOutcome) -> Result<()> {
+    for date in outcome.summarized {
+        log!(logger, "Logfiles summarized", date)?;
+    }
+    for pruned in outcome.pruned {
+        match pruned.error {
+            Some(error) => log!(logger, "Logfile removal failed", file = pruned.file, error)?,
+            None => log!(
+                logger,
+                "Logfile removed",
+                file = pruned.file,
+                size = pruned.size,
+                reason = pruned.reason
+            )?,
+        }
+    }
+
+    Ok(())
+}
+
+/// Connectivity as reported by the monitor. It only flips between online and offline once the
+/// debounce rules confirm it.
+#[derive(Debug, Clone, Copy)]
+struct ObservedState {
+    connectivity: Connectivity,
+    debouncer: Debouncer,
+    schedule: Schedule,
+    /// Start of the current connectivity.
+    since: DateTime<Utc>,
+    /// First check of a streak that is not confirmed yet.
+    candidate: Option<DateTime<Utc>>,
+    clock: ClockReading,
+}
+
+/// Wall clock and monotonic clock at the time of a check.
+#[derive(Debug, Clone, Copy)]
+struct ClockReading {
+    wall: DateTime<Utc>,
+    monotonic: Instant,
+}
+
+impl ClockReading {
+    fn at(result: &InternetCheckResult) -> Self {
+        Self {
+            wall: result.timestamp,
+            monotonic: Instant::now(),
+        }
+    }
+
+    /// Detects a suspend or clock jump since the earlier reading. The monotonic clock stops while
+    /// the machine is suspended and is not affected by changes of the system time.
+    fn jump_since(&self, earlier: &ClockReading) -> Option<ClockJump> {
+        let wall = self.wall - earlier.wall;
+        let monotonic = TimeDelta::from_std(self.monotonic - earlier.monotonic).ok()?;
+        let drift = (wall - monotonic).num_seconds();
+
+        (drift.abs() > CLOCK_JUMP_TOLERANCE).then_some(ClockJump {
+            since: earlier.wall,
+            until: self.wall,
+            drift,
+        })
+    }
+}
+
+/// Interval until the next check and the reason it was picked.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+struct Schedule {
+    mode: ScheduleMode,
+    interval: u64,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum ScheduleMode {
+    Regular,
+    Backoff,
+    Offline,
+    Degraded,
+}
+
+impl Schedule {
+    /// Picks the offline or degraded interval while the connection is down, degraded or an outage
+    /// is about to be confirmed. While the connection is stable, the interval doubles up to the
+    /// max interval, if one is set.
+    fn next(config: &MonitorConfig, state: &ObservedState, stable: bool) -> Self {
+        let backing_off = matches!(
+            state.schedule.mode,
+            ScheduleMode::Regular | ScheduleMode::Backoff
+        );
+
+        let (mode, interval) = match (state.connectivity, stable, config.max_interval) {
+            (Connectivity::Offline, _, _) | (_, false, _) => (
+                ScheduleMode::Offline,
+                config.offline_interval.unwrap_or(config.interval),
+            ),
+            (Connectivity::Degraded, _, _) => (
+                ScheduleMode::Degraded,
+                config.degraded_interval.unwrap_or(config.interval),
+            ),
+            (Connectivity::Online, _, Some(max)) if backing_off => (
+                ScheduleMode::Backoff,
+                (state.schedule.interval * 2).clamp(config.interval, max.max(config.interval)),
+            ),
+            (Connectivity::Online, _, _) => (ScheduleMode::Regular, config.interval),
+        };
+
+        Self {
+            mode,
+            interval: interval.max(1),
+        }
+    }
+
+    /// Whether the schedule changed in a way worth logging. While backing off, the interval
+    /// changes after every check, so only reaching the max interval is.
+    fn is_notable_change(&self, previous: &Schedule, config: &MonitorConfig) -> bool {
+        match (previous.mode, self.mode) {
+            _ if previous == self => false,
+            (ScheduleMode::Backoff, ScheduleMode::Backoff) => config
+                .max_interval
+                .is_some_and(|max| self.interval == max.max(config.interval).max(1)),
+            _ => true,
+        }
+    }
+
+    fn regular(config: &MonitorConfig) -> Self {
+        Self {
+            mode: ScheduleMode::Regular,
+            interval: config.interval,
+        }
+    }
+
+    fn duration(&self) -> Duration {
+        Duration::from_secs(self.interval)
+    }
+}
+
 async fn observe_connection(
-    client: Client,
     logger: Arc<Logger>,
-    previous: Option<Connectivity>,
-) -> Result<Connectivity> {
+    config: Arc<Config>,
+    notifier: Arc<Notifier>,
+    status: Arc<MonitorStatus>,
+    notify_socket: &NotifySocket,
+    previous: Option<ObservedState>,
+) -> Result<ObservedState> {
     let check_cycle = match previous {
         Some(_) => InternetCheckCycle::Started,
         None => InternetCheckCycle::Running,
     };
-    let result = check_connection(client.clone(), None, check_cycle).await;
+    let result = check(&config, check_cycle).await;
+    let clock = ClockReading::at(&result);
 
-    match (previous, result.connectivity()) {
-        (None, connectivity) => match connectivity {
-            Connectivity::Online => log!(logger, "Started - Internet available", result)?,
-            Connectivity::Offline => log!(logger, "Started - Internet unavailable", result)?,
-        },
-        (Some(_), Connectivity::Offline) => log!(logger, "Internet unavailable", result)?,
-        (Some(Connectivity::Offline), Connectivity::Online) => {
-            log!(logger, "Internet restored", result)?
+    let (mut state, stable) = match previous {
+        Some(mut state) => {
+            if let Some(clock_jump) = clock.jump_since(&state.clock) {
+                log!(logger, "Suspend or clock jump detected", clock_jump)?;
+            }
+            state.clock = clock;
+
+            let (step, event) =
+                observe_transition(&logger, config.monitor.record, &mut state, &result)?;
+            if let Some(event) = event {
+                notifier.notify(&event);
+            }
+            notifier.observe(result.connectivity());
+
+            (state, !matches!(step, DebounceStep::Pending { .. }))
         }
-        _ => {}
+        None => {
+            match result.connectivity() {
+                Connectivity::Online => log!(logger, "Started - Internet available", result)?,
+                Connectivity::Degraded => log!(logger, "Started - Internet degraded", result)?,
+                Connectivity::Offline => log!(logger, "Started - Internet unavailable", result)?,
+            }
+
+            let state = ObservedState {
+                connectivity: result.connectivity(),
+                debouncer: Debouncer::new(
+                    &config.debounce,
+                    result.connectivity() == Connectivity::Offline,
+                ),
+                schedule: Schedule::regular(&config.monitor),
+                since: result.timestamp,
+                candidate: None,
+                clock,
+            };
+            (state, true)
+        }
+    };
+
+    status.record(&result, state.connectivity, state.since)?;
+
+    let service_status = format!(
+        "{} since {}",
+        state.connectivity,
+        state
+            .since
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M:%S")
+    );
+    let notified = match previous {
+        Some(_) => notify_socket.status(&service_status),
+        None => notify_socket.ready(&service_status),
+    };
+    if let Err(err) = notified {
+        log!(
+            logger,
+            "Systemd notification failed",
+            error = format!("{err:#}")
+        )?;
+    }
+
+    let schedule = Schedule::next(&config.monitor, &state, stable);
+    if previous.is_none_or(|p| schedule.is_notable_change(&p.schedule, &config.monitor)) {
+        log!(logger, "Check schedule changed", schedule)?;
     }
+    state.schedule = schedule;
 
     logger.sync()?;
 
-    Ok(result.connectivity())
+    Ok(state)
+}
+
+/// Logs the check, if it changes the connectivity or belongs to a streak that could change it.
+/// Other checks are only logged as the record mode demands. Returns the event for the hooks, if
+/// the connectivity changed.
+fn observe_transition(
+    logger: &Logger,
+    record: RecordMode,
+    state: &mut ObservedState,
+    result: &InternetCheckResult,
+) -> Result<(DebounceStep, Option<TransitionEvent>)> {
+    let connectivity = result.connectivity();
+    let step = state
+        .debouncer
+        .update(connectivity == Connectivity::Offline);
+
+    // Like in the reports, a period starts at the first check of the streak that confirmed it.
+    match step {
+        DebounceStep::Pending { streak: 1, .. } => state.candidate = Some(result.timestamp),
+        DebounceStep::Interrupted => state.candidate = None,
+        _ => {}
+    }
+    let changed_at = match step {
+        DebounceStep::Flipped => state.candidate.take().unwrap_or(result.timestamp),
+        _ => result.timestamp,
+    };
+
+    // Every check of an unconfirmed streak is logged, so the reports can apply the same rules.
+    let transition = match (step, state.connectivity, connectivity) {
+        (DebounceStep::Pending { streak, required }, Connectivity::Offline, _) => {
+            log!(
+                logger,
+                format!("Internet available, waiting for confirmation ({streak}/{required})"),
+                result
+            )?;
+            None
+        }
+        (DebounceStep::Pending { streak, required }, _, _) => {
+            log!(
+                logger,
+                format!("Internet unavailable, waiting for confirmation ({streak}/{required})"),
+                result
+            )?;
+            None
+        }
+        (DebounceStep::Interrupted, Connectivity::Offline, _) => {
+            log!(
+                logger,
+                "Internet unavailable, restore not confirmed",
+                result
+            )?;
+            None
+        }
+        // A check that ends an unconfirmed outage may still move between online and degraded.
+        (
+            DebounceStep::Steady | DebounceStep::Interrupted,
+            Connectivity::Online,
+            Connectivity::Degraded,
+        ) => {
+            log!(logger, "Internet degraded", result)?;
+            Some(Transition::Degraded)
+        }
+        (
+            DebounceStep::Steady | DebounceStep::Interrupted,
+            Connectivity::Degraded,
+            Connectivity::Online,
+        ) => {
+            log!(logger, "Internet no longer degraded", result)?;
+            Some(Transition::Recovered)
+        }
+        (DebounceStep::Interrupted, _, _) => {
+            log!(logger, "Internet available, outage not confirmed", result)?;
+            None
+        }
+        (DebounceStep::Flipped, _, Connectivity::Offline) => {
+            log!(logger, "Internet unavailable", result)?;
+            Some(Transition::Offline)
+        }
+        (_, _, Connectivity::Offline) => {
+            log!(logger, "Internet unavailable", result)?;
+            None
+        }
+        (DebounceStep::Flipped, _, Connectivity::Online) => {
+            log!(logger, "Internet restored", result)?;
+            Some(Transition::Restored)
+        }
+        (DebounceStep::Flipped, _, Connectivity::Degraded) => {
+            log!(logger, "Internet restored, but degraded", result)?;
+            Some(Transition::Restored)
+        }
+        _ => {
+            record_check(logger, record, result)?;
+            None
+        }
+    };
+
+    let event = transition.map(|transition| match transition {
+        Transition::Restored | Transition::Recovered => {
+            TransitionEvent::new(transition, result, state.since, Some(changed_at))
+        }
+        _ => TransitionEvent::new(transition, result, changed_at, None),
+    });
+
+    if !matches!(step, DebounceStep::Pending { .. }) {
+        if state.connectivity != connectivity {
+            state.since = changed_at;
+        }
+        state.connectivity = connectivity;
+    }
+
+    Ok((step, event))
+}
+
+fn record_check(logger: &Logger, record: RecordMode, result: &InternetCheckResult) -> Result<()> {
+    match record {
+        RecordMode::Transitions => Ok(()),
+        RecordMode::Full => log!(logger, "Internet checked", result),
+        RecordMode::Sample => log!(logger, "Internet checked", result = result.sample()),
+    }
+}
+
+async fn check(config: &Config, check_cycle: InternetCheckCycle) -> InternetCheckResult {
+    check_connection(
+        &config.targets,
+        &config.degraded,
+        Duration::from_secs(config.monitor.timeout),
+        config.monitor.latency_threshold,
+        check_cycle,
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::DebounceConfig, log::LogMode, model::LatencySpeed};
+
+    fn logger() -> Logger {
+        Logger::builder()
+            .with_dir(std::env::temp_dir().join("netcheck-test-monitor"))
+            .with_mode(LogMode::Silent)
+            .build()
+            .unwrap()
+    }
+
+    fn result(connectivity: Connectivity, minute: i64) -> InternetCheckResult {
+        let mut result = InternetCheckResult::new(
+            connectivity,
+            LatencySpeed::Ok,
+            500,
+            Vec::new(),
+            Duration::ZERO,
+            InternetCheckCycle::Running,
+        );
+        result.timestamp = DateTime::UNIX_EPOCH + TimeDelta::minutes(minute);
+
+        result
+    }
+
+    /// State that needs two failed checks to confirm an outage.
+    fn state(connectivity: Connectivity) -> ObservedState {
+        let debounce = DebounceConfig {
+            failures: 2,
+            successes: 1,
+        };
+        let first = result(connectivity, 0);
+
+        ObservedState {
+            connectivity,
+            debouncer: Debouncer::new(&debounce, false),
+            schedule: Schedule::regular(&MonitorConfig::default()),
+            since: first.timestamp,
+            candidate: None,
+            clock: ClockReading::at(&first),
+        }
+    }
+
+    /// Runs the checks and returns the transitions with the start and end of their periods.
+    fn observe(state: &mut ObservedState, checks: &[Connectivity]) -> Vec<serde_json::Value> {
+        let logger = logger();
+
+        checks
+            .iter()
+            .enumerate()
+            .filter_map(|(minute, connectivity)| {
+                let result = result(*connectivity, minute as i64 + 1);
+                let (_, event) =
+                    observe_transition(&logger, RecordMode::Transitions, state, &result).unwrap();
+                event.map(|event| serde_json::to_value(event).unwrap())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn degrades_when_an_unconfirmed_outage_ends_degraded() {
+        let mut state = state(Connectivity::Online);
+
+        let events = observe(&mut state, &[Connectivity::Offline, Connectivity::Degraded]);
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["transition"], "degraded");
+        assert_eq!(events[0]["outage_start"], "1970-01-01T00:02:00Z");
+        assert_eq!(state.connectivity, Connectivity::Degraded);
+    }
+
+    #[test]
+    fn recovers_when_an_unconfirmed_outage_ends_online() {
+        let mut state = state(Connectivity::Degraded);
+
+        let events = observe(&mut state, &[Connectivity::Offline, Connectivity::Online]);
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["transition"], "recovered");
+        assert_eq!(events[0]["outage_start"], "1970-01-01T00:00:00Z");
+        assert_eq!(events[0]["outage_end"], "1970-01-01T00:02:00Z");
+        assert_eq!(state.connectivity, Connectivity::Online);
+    }
 }
@@ -0,0 +1,98 @@
+use std::{process::Stdio, sync::Arc, time::Duration};
+
+use anyhow::{Context, Result};
+use tokio::{io::AsyncWriteExt, process::Command, time::timeout as with_timeout};
+
+use crate::{
+    config::{DEFAULT_HOOK_TIMEOUT, HookConfig},
+    log::Logger,
+    notify::TransitionEvent,
+};
+
+/// Runs the hook command and logs its exit status. The event is handed over as JSON on stdin and
+/// as `NETCHECK_*` environment variables.
+pub async fn run(logger: Arc<Logger>, hook: HookConfig, event: TransitionEvent) {
+    let timeout = Duration::from_secs(hook.timeout.unwrap_or(DEFAULT_HOOK_TIMEOUT));
+    let transition = event.transition.as_str();
+    let command = &hook.command;
+
+    let logged = match with_timeout(timeout, execute(&hook, &event, logger.has_terminal())).await {
+        Ok(Ok(status)) => log!(
+            logger,
+            "Hook finished",
+            command,
+            transition,
+            status = status.code(),
+            success = status.success()
+        ),
+        Ok(Err(err)) => log!(
+            logger,
+            "Hook failed",
+            command,
+            transition,
+            error = format!("{err:#}")
+        ),
+        Err(_) => log!(
+            logger,
+            "Hook timed out",
+            command,
+            transition,
+            timeout = timeout.as_secs()
+        ),
+    };
+
+    if let Err(err) = logged.and_then(|_| logger.sync()) {
+        logger.print_error(&format!("Failed to log hook result: {err}"));
+    }
+}
+
+/// The output of the hook is discarded, except for stderr while no dashboard draws on the terminal.
+async fn execute(
+    hook: &HookConfig,
+    event: &TransitionEvent,
+    terminal: bool,
+) -> Result<std::process::ExitStatus> {
+    let failed_targets = event
+        .failed_targets
+        .iter()
+        .map(|t| t.target.as_str())
+        .collect::<Vec<&str>>()
+        .join(",");
+
+    let mut child = Command::new(&hook.command)
+        .args(&hook.args)
+        // The systemd notification socket and watchdog belong to the monitor, not to its hooks.
+        .env_remove("NOTIFY_SOCKET")
+        .env_remove("WATCHDOG_USEC")
+        .env_remove("WATCHDOG_PID")
+        .env("NETCHECK_TRANSITION", event.transition.as_str())
+        .env("NETCHECK_TIMESTAMP", event.timestamp.to_rfc3339())
+        .env("NETCHECK_CONNECTIVITY", format!("{:?}", event.connectivity))
+        .env("NETCHECK_OUTAGE_START", event.outage_start.to_rfc3339())
+        .env(
+            "NETCHECK_OUTAGE_END",
+            event.outage_end.map(|t| t.to_rfc3339()).unwrap_or_default(),
+        )
+        .env(
+            "NETCHECK_OUTAGE_DURATION",
+            event.outage_duration.to_string(),
+        )
+        .env("NETCHECK_FAILED_TARGETS", failed_targets)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(match terminal {
+            true => Stdio::inherit(),
+            false => Stdio::null(),
+        })
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("Failed to start hook '{}'", hook.command))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        let payload = serde_json::to_vec(event)?;
+        // A hook that ignores stdin may exit before reading it, which is not an error.
+        let _ = stdin.write_all(&payload).await;
+    }
+
+    Ok(child.wait().await?)
+}
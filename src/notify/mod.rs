@@ -0,0 +1,141 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::{Config, HookConfig},
+    log::Logger,
+    model::{Connectivity, InternetCheckResult},
+    notify::webhook::WebhookQueue,
+    time::period_duration,
+};
+
+mod hook;
+mod webhook;
+
+/// Connectivity transitions the monitor notifies about. `Restored` ends an outage, `Recovered` a
+/// degraded period of a connection that stayed up.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Transition {
+    Offline,
+    Degraded,
+    Restored,
+    Recovered,
+}
+
+impl Transition {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Transition::Offline => "offline",
+            Transition::Degraded => "degraded",
+            Transition::Restored => "restored",
+            Transition::Recovered => "recovered",
+        }
+    }
+}
+
+/// Describes a transition for hooks and webhooks. The outage is the period the transition started or, when the
+/// connection is restored, the period that just ended.
+#[derive(Serialize, Debug, Clone)]
+pub struct TransitionEvent {
+    transition: Transition,
+    timestamp: DateTime<Utc>,
+    connectivity: Connectivity,
+    outage_start: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    outage_end: Option<DateTime<Utc>>,
+    /// Duration of the outage in seconds, until now if it has not ended yet.
+    outage_duration: i64,
+    failed_targets: Vec<FailedTarget>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+struct FailedTarget {
+    target: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl TransitionEvent {
+    pub fn new(
+        transition: Transition,
+        result: &InternetCheckResult,
+        outage_start: DateTime<Utc>,
+        outage_end: Option<DateTime<Utc>>,
+    ) -> Self {
+        let failed_targets = result
+            .iter_failed_targets()
+            .map(|r| FailedTarget {
+                target: r.target().to_string(),
+                error: r.error().map(|e| e.to_string()),
+            })
+            .collect();
+        let duration = period_duration(&outage_start, &outage_end.unwrap_or(result.timestamp));
+
+        Self {
+            transition,
+            timestamp: result.timestamp,
+            connectivity: result.connectivity(),
+            outage_start,
+            outage_end,
+            outage_duration: duration.num_seconds(),
+            failed_targets,
+        }
+    }
+}
+
+/// Notifies hooks and webhooks about transitions.
+pub struct Notifier {
+    logger: Arc<Logger>,
+    hooks: Vec<HookConfig>,
+    webhooks: Option<WebhookQueue>,
+}
+
+impl Notifier {
+    /// Creates the notifier and starts the webhook queue, if any webhook is configured.
+    pub fn new(logger: Arc<Logger>, config: &Config) -> Self {
+        let webhooks = match config.webhooks.is_empty() {
+            true => None,
+            false => Some(WebhookQueue::start(
+                Arc::clone(&logger),
+                config.webhooks.clone(),
+            )),
+        };
+
+        Self {
+            logger,
+            hooks: config.hooks.clone(),
+            webhooks,
+        }
+    }
+
+    /// Starts all hooks configured for the transition in the background, so slow hooks do not
+    /// delay the next check, and queues the webhooks.
+    pub fn notify(&self, event: &TransitionEvent) {
+        self.hooks
+            .iter()
+            .filter(|hook| hook.on.is_empty() || hook.on.contains(&event.transition))
+            .for_each(|hook| {
+                tokio::spawn(hook::run(
+                    Arc::clone(&self.logger),
+                    hook.clone(),
+                    event.clone(),
+                ));
+            });
+
+        if let Some(webhooks) = &self.webhooks {
+            webhooks.queue(event);
+        }
+    }
+
+    /// Called after every check. Queued webhooks are only delivered while the connection is up.
+    pub fn observe(&self, connectivity: Connectivity) {
+        if let Some(webhooks) = &self.webhooks
+            && connectivity != Connectivity::Offline
+        {
+            webhooks.deliver();
+        }
+    }
+}
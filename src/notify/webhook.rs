@@ -0,0 +1,361 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use futures::future::join_all;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+
+use crate::{
+    check::post_json,
+    config::{DEFAULT_WEBHOOK_TIMEOUT, WebhookConfig},
+    log::Logger,
+    model::CheckError,
+    notify::{Transition, TransitionEvent},
+};
+
+/// Notifications kept per endpoint while the connection or the endpoint is down.
+const MAX_QUEUED: usize = 100;
+/// Wait after the first failed attempt, doubled after every further one.
+const RETRY_MIN: Duration = Duration::from_secs(10);
+const RETRY_MAX: Duration = Duration::from_secs(600);
+
+enum Command {
+    Queue(TransitionEvent),
+    Deliver,
+}
+
+struct Pending {
+    webhook: WebhookConfig,
+    transition: Transition,
+    body: Vec<u8>,
+}
+
+/// Hands notifications to a background task, which keeps them queued until they could be
+/// delivered.
+pub struct WebhookQueue {
+    sender: UnboundedSender<Command>,
+}
+
+impl WebhookQueue {
+    pub fn start(logger: Arc<Logger>, webhooks: Vec<WebhookConfig>) -> Self {
+        let (sender, receiver) = unbounded_channel();
+        tokio::spawn(run(logger, webhooks, receiver));
+
+        Self { sender }
+    }
+
+    pub fn queue(&self, event: &TransitionEvent) {
+        let _ = self.sender.send(Command::Queue(event.clone()));
+    }
+
+    /// Tries to deliver all queued notifications.
+    pub fn deliver(&self) {
+        let _ = self.sender.send(Command::Deliver);
+    }
+}
+
+async fn run(
+    logger: Arc<Logger>,
+    webhooks: Vec<WebhookConfig>,
+    mut receiver: UnboundedReceiver<Command>,
+) {
+    let mut deliveries = Deliveries::default();
+
+    while let Some(command) = receiver.recv().await {
+        match command {
+            Command::Queue(event) => {
+                let Ok(body) = serde_json::to_vec(&event) else {
+                    continue;
+                };
+
+                webhooks
+                    .iter()
+                    .filter(|w| w.on.is_empty() || w.on.contains(&event.transition))
+                    .for_each(|webhook| {
+                        deliveries.push(
+                            &logger,
+                            Pending {
+                                webhook: webhook.clone(),
+                                transition: event.transition,
+                                body: body.clone(),
+                            },
+                        )
+                    });
+            }
+            Command::Deliver => deliveries.deliver(&logger).await,
+        }
+    }
+}
+
+/// Notifications per webhook URL, so an endpoint that is down doesn't hold up the others.
+#[derive(Default)]
+struct Deliveries(HashMap<String, Delivery>);
+
+impl Deliveries {
+    fn push(&mut self, logger: &Logger, pending: Pending) {
+        self.0
+            .entry(pending.webhook.url.clone())
+            .or_default()
+            .push(logger, pending);
+    }
+
+    /// Delivers to all endpoints at the same time.
+    async fn deliver(&mut self, logger: &Logger) {
+        join_all(self.0.values_mut().map(|delivery| delivery.deliver(logger))).await;
+    }
+}
+
+/// Queued notifications of one endpoint and the backoff after failed attempts.
+#[derive(Default)]
+struct Delivery {
+    queue: VecDeque<Pending>,
+    failures: u32,
+    retry_at: Option<Instant>,
+}
+
+impl Delivery {
+    /// Queues the notification. Once the queue is full, the oldest notification is dropped.
+    fn push(&mut self, logger: &Logger, pending: Pending) {
+        if self.queue.len() >= MAX_QUEUED
+            && let Some(dropped) = self.queue.pop_front()
+        {
+            let logged = log!(
+                logger,
+                "Webhook queue full, dropped oldest notification",
+                url = dropped.webhook.url,
+                transition = dropped.transition.as_str()
+            );
+            if let Err(err) = logged {
+                logger.print_error(&format!("Failed to log webhook result: {err}"));
+            }
+        }
+
+        self.queue.push_back(pending);
+    }
+
+    /// Sends the queued notifications in order. Stops at the first one that could not be sent,
+    /// since the connection or the endpoint is likely down, and waits longer after every failed
+    /// attempt. Notifications the endpoint rejected are dropped.
+    async fn deliver(&mut self, logger: &Logger) {
+        if self
+            .retry_at
+            .is_some_and(|retry_at| Instant::now() < retry_at)
+        {
+            return;
+        }
+
+        while let Some(pending) = self.queue.front() {
+            let url = &pending.webhook.url;
+            let transition = pending.transition.as_str();
+            let timeout = pending.webhook.timeout.unwrap_or(DEFAULT_WEBHOOK_TIMEOUT);
+
+            let result = post_json(url, &pending.body, Duration::from_secs(timeout)).await;
+            let (logged, done) = match result {
+                Ok(status) => (
+                    log!(logger, "Webhook delivered", url, transition, status),
+                    true,
+                ),
+                Err(CheckError::HttpStatus(status)) if !is_retryable(status) => (
+                    log!(logger, "Webhook rejected", url, transition, status),
+                    true,
+                ),
+                Err(error) => {
+                    self.failures += 1;
+                    let backoff = RETRY_MIN
+                        .saturating_mul(2u32.saturating_pow(self.failures - 1))
+                        .min(RETRY_MAX);
+                    self.retry_at = Some(Instant::now() + backoff);
+
+                    (
+                        log!(
+                            logger,
+                            "Webhook failed, kept in queue",
+                            url,
+                            transition,
+                            error = error.to_string(),
+                            queued = self.queue.len(),
+                            retry_in = backoff.as_secs()
+                        ),
+                        false,
+                    )
+                }
+            };
+
+            if let Err(err) = logged.and_then(|_| logger.sync()) {
+                logger.print_error(&format!("Failed to log webhook result: {err}"));
+            }
+            if !done {
+                break;
+            }
+
+            self.queue.pop_front();
+            self.failures = 0;
+            self.retry_at = None;
+        }
+    }
+}
+
+/// Server errors and rate limits are temporary, every other status is a rejection.
+fn is_retryable(status: u16) -> bool {
+    status == 429 || (500..600).contains(&status)
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+        task::JoinHandle,
+    };
+
+    use super::*;
+    use crate::log::LogMode;
+
+    /// Stand-in endpoint that answers one request with each status and returns the bodies it got.
+    async fn serve(statuses: &[u16]) -> (String, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let statuses = statuses.to_vec();
+
+        let server = tokio::spawn(async move {
+            let mut bodies = Vec::new();
+
+            for status in statuses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                let mut buffer = [0; 1024];
+
+                let body = loop {
+                    let len = stream.read(&mut buffer).await.unwrap();
+                    request.extend_from_slice(&buffer[..len]);
+
+                    let Some(end) = request.windows(4).position(|w| w == b"\r\n\r\n") else {
+                        continue;
+                    };
+                    let head = String::from_utf8_lossy(&request[..end]).to_lowercase();
+                    let content_length = head
+                        .lines()
+                        .find_map(|line| line.strip_prefix("content-length:"))
+                        .and_then(|value| value.trim().parse::<usize>().ok())
+                        .unwrap_or(0);
+                    if let Some(body) = request.get(end + 4..end + 4 + content_length) {
+                        break String::from_utf8_lossy(body).into_owned();
+                    }
+                };
+                bodies.push(body);
+
+                let response = format!(
+                    "HTTP/1.1 {status} Status\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+
+            bodies
+        });
+
+        (url, server)
+    }
+
+    fn logger(name: &str) -> Logger {
+        Logger::builder()
+            .with_dir(std::env::temp_dir().join(format!("netcheck-test-{name}")))
+            .with_mode(LogMode::Silent)
+            .build()
+            .unwrap()
+    }
+
+    fn pending(url: &str, body: &str) -> Pending {
+        Pending {
+            webhook: WebhookConfig {
+                url: url.to_string(),
+                on: Vec::new(),
+                timeout: Some(5),
+            },
+            transition: Transition::Restored,
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    #[tokio::test]
+    async fn retries_server_errors_after_backoff() {
+        let logger = logger("webhook-retry");
+        let (url, server) = serve(&[503, 429, 200, 200]).await;
+        let mut delivery = Delivery::default();
+        delivery.push(&logger, pending(&url, r#"{"n":1}"#));
+        delivery.push(&logger, pending(&url, r#"{"n":2}"#));
+
+        delivery.deliver(&logger).await;
+        assert_eq!(delivery.queue.len(), 2);
+        assert_eq!(delivery.failures, 1);
+
+        // Still backing off, so nothing is sent.
+        delivery.deliver(&logger).await;
+        assert_eq!(delivery.failures, 1);
+
+        delivery.retry_at = None;
+        delivery.deliver(&logger).await;
+        assert_eq!(delivery.queue.len(), 2);
+        assert_eq!(delivery.failures, 2);
+
+        delivery.retry_at = None;
+        delivery.deliver(&logger).await;
+        assert!(delivery.queue.is_empty());
+        assert_eq!(delivery.failures, 0);
+
+        let bodies = server.await.unwrap();
+        assert_eq!(
+            bodies,
+            [r#"{"n":1}"#, r#"{"n":1}"#, r#"{"n":1}"#, r#"{"n":2}"#]
+        );
+    }
+
+    #[tokio::test]
+    async fn drops_rejected_notifications() {
+        let logger = logger("webhook-rejected");
+        let (url, server) = serve(&[404, 204]).await;
+        let mut delivery = Delivery::default();
+        delivery.push(&logger, pending(&url, r#"{"n":1}"#));
+        delivery.push(&logger, pending(&url, r#"{"n":2}"#));
+
+        delivery.deliver(&logger).await;
+        assert!(delivery.queue.is_empty());
+        assert_eq!(delivery.failures, 0);
+        assert_eq!(server.await.unwrap(), [r#"{"n":1}"#, r#"{"n":2}"#]);
+    }
+
+    #[tokio::test]
+    async fn drops_the_oldest_notification_when_full() {
+        let logger = logger("webhook-full");
+        let mut delivery = Delivery::default();
+
+        for n in 0..=MAX_QUEUED {
+            delivery.push(&logger, pending("http://127.0.0.1:9/hook", &n.to_string()));
+        }
+
+        assert_eq!(delivery.queue.len(), MAX_QUEUED);
+        assert_eq!(delivery.queue.front().unwrap().body, b"1");
+    }
+
+    #[tokio::test]
+    async fn keeps_delivering_to_healthy_endpoints() {
+        let logger = logger("webhook-endpoints");
+        let (failing, failing_server) = serve(&[503]).await;
+        let (healthy, healthy_server) = serve(&[200, 200]).await;
+        let mut deliveries = Deliveries::default();
+        for n in 1..=2 {
+            let body = format!(r#"{{"n":{n}}}"#);
+            deliveries.push(&logger, pending(&failing, &body));
+            deliveries.push(&logger, pending(&healthy, &body));
+        }
+
+        deliveries.deliver(&logger).await;
+
+        assert_eq!(deliveries.0[&failing].queue.len(), 2);
+        assert_eq!(deliveries.0[&failing].failures, 1);
+        assert!(deliveries.0[&healthy].queue.is_empty());
+        assert_eq!(failing_server.await.unwrap(), [r#"{"n":1}"#]);
+        assert_eq!(healthy_server.await.unwrap(), [r#"{"n":1}"#, r#"{"n":2}"#]);
+    }
+}
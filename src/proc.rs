@@ -0,0 +1,38 @@
+use std::path::Path;
+
+/// Processes that hold the file open, with their pid and name. Without `/proc`, this can't be
+/// checked and `None` is returned. Processes of other users are missed without privileges.
+#[cfg(target_os = "linux")]
+pub fn open_by(path: &Path) -> Option<Vec<(u32, String)>> {
+    let processes = std::fs::read_dir("/proc").ok()?;
+    let Ok(path) = path.canonicalize() else {
+        return Some(Vec::new());
+    };
+    let own = std::process::id();
+
+    let open_by = processes
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let pid = entry.file_name().to_str()?.parse::<u32>().ok()?;
+            (pid != own).then_some((pid, entry.path()))
+        })
+        .filter(|(_, dir)| {
+            // Descriptors of processes of other users can't be read without privileges.
+            std::fs::read_dir(dir.join("fd")).is_ok_and(|fds| {
+                fds.filter_map(|fd| fd.ok())
+                    .any(|fd| std::fs::read_link(fd.path()).is_ok_and(|target| target == path))
+            })
+        })
+        .map(|(pid, dir)| {
+            let name = std::fs::read_to_string(dir.join("comm")).unwrap_or_default();
+            (pid, name.trim().to_string())
+        })
+        .collect();
+
+    Some(open_by)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn open_by(_path: &Path) -> Option<Vec<(u32, String)>> {
+    None
+}
@@ -1,8 +1,10 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use anyhow::Result;
 use directories::ProjectDirs;
 
+use crate::config::DEFAULT_CONFIG_FILE;
+
 pub struct Project {
     project_dirs: ProjectDirs,
 }
@@ -18,4 +20,12 @@ impl Project {
     pub fn log_dir(&self) -> &Path {
         self.project_dirs.data_local_dir()
     }
+
+    pub fn config_dir(&self) -> &Path {
+        self.project_dirs.config_dir()
+    }
+
+    pub fn config_file(&self) -> PathBuf {
+        self.config_dir().join(DEFAULT_CONFIG_FILE)
+    }
 }
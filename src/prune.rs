@@ -0,0 +1,366 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Display,
+    fs::{File, OpenOptions, create_dir_all, read_dir, remove_file},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app::prune::PruneArgs,
+    archive,
+    config::{Config, RetentionConfig},
+    model::Report,
+    project::Project,
+    sort::parse_logfile_name,
+};
+
+pub const SUMMARY_DIR: &str = "summaries";
+pub const SUMMARY_FILE: &str = "daily.jsonl";
+
+pub fn run(args: PruneArgs, config: Config, project: Project) -> Result<()> {
+    if !config.retention.is_enabled() {
+        return Err(anyhow::anyhow!(
+            "No limit set, use --max-age, --max-size, --max-files or [retention] in the config"
+        ));
+    }
+
+    let dir = config.log_dir(&project);
+    let outcome = prune(dir, &config, args.dry_run)?;
+
+    for day in &outcome.summarized {
+        match args.dry_run {
+            true => println!("Would summarize {day}"),
+            false => println!(
+                "Summarized {day} into '{}'",
+                dir.join(SUMMARY_DIR).join(SUMMARY_FILE).display()
+            ),
+        }
+    }
+    for pruned in &outcome.pruned {
+        match (&pruned.error, args.dry_run) {
+            (Some(err), _) => println!("Failed to remove '{}': {err}", pruned.file.display()),
+            (None, true) => println!("Would remove '{}' ({pruned})", pruned.file.display()),
+            (None, false) => println!("Removed '{}' ({pruned})", pruned.file.display()),
+        }
+    }
+
+    let removed = outcome
+        .pruned
+        .iter()
+        .filter(|pruned| pruned.error.is_none())
+        .collect::<Vec<_>>();
+    let size = removed.iter().map(|pruned| pruned.size).sum::<u64>();
+    match args.dry_run {
+        true => println!(
+            "Would free {} in {} logfiles",
+            format_size(size),
+            removed.len()
+        ),
+        false => println!("Freed {} in {} logfiles", format_size(size), removed.len()),
+    }
+
+    Ok(())
+}
+
+/// Logfiles removed by the retention limits, and the days summarized before.
+#[derive(Debug, Default)]
+pub struct PruneOutcome {
+    pub pruned: Vec<Pruned>,
+    pub summarized: Vec<NaiveDate>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Pruned {
+    pub file: PathBuf,
+    pub size: u64,
+    pub reason: PruneReason,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Display for Pruned {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}, {}", format_size(self.size), self.reason)
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PruneReason {
+    Age,
+    Count,
+    Size,
+}
+
+impl Display for PruneReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PruneReason::Age => write!(f, "too old"),
+            PruneReason::Count => write!(f, "too many logfiles"),
+            PruneReason::Size => write!(f, "logfiles too large"),
+        }
+    }
+}
+
+/// Removes the logfiles of the monitor in `dir` that exceed the retention limits, starting with the
+/// oldest. Only the files with the monitor's prefix count, and the newest of them is always kept,
+/// since the monitor may still write into it. With a dry run, nothing is written or removed.
+pub fn prune(dir: &Path, config: &Config, dry_run: bool) -> Result<PruneOutcome> {
+    let logfiles = collect_logfiles(dir, &config.log.filename)?;
+    let files_per_day = count_per_day(logfiles.iter());
+    let mut selected = select(logfiles, &config.retention, Local::now().date_naive());
+
+    if config.retention.summarize {
+        // A day is summarized once from all of its logfiles, so it's only pruned as a whole.
+        let selected_per_day = count_per_day(selected.iter().map(|(logfile, _)| logfile));
+        selected
+            .retain(|(logfile, _)| selected_per_day[&logfile.date] == files_per_day[&logfile.date]);
+    }
+    if selected.is_empty() {
+        return Ok(PruneOutcome::default());
+    }
+
+    let mut outcome = PruneOutcome::default();
+    if config.retention.summarize {
+        let summarized = summarized_days(dir)?;
+        let days = selected.iter().fold(
+            BTreeMap::<NaiveDate, Vec<PathBuf>>::new(),
+            |mut days, (logfile, _)| {
+                days.entry(logfile.date)
+                    .or_default()
+                    .push(logfile.path.clone());
+                days
+            },
+        );
+
+        // A day whose logfiles couldn't all be removed last time is summarized already.
+        for (day, paths) in days
+            .into_iter()
+            .filter(|(day, _)| !summarized.contains(day))
+        {
+            if !dry_run {
+                write_summary(dir, day, paths, config)
+                    .with_context(|| format!("Failed to summarize {day}, nothing was removed"))?;
+            }
+            outcome.summarized.push(day);
+        }
+    }
+
+    for (logfile, reason) in selected {
+        let error = match dry_run {
+            true => None,
+            false => remove_file(&logfile.path).err().map(|err| err.to_string()),
+        };
+
+        outcome.pruned.push(Pruned {
+            file: logfile.path,
+            size: logfile.size,
+            reason,
+            error,
+        });
+    }
+
+    Ok(outcome)
+}
+
+struct Logfile {
+    path: PathBuf,
+    date: NaiveDate,
+    index: u64,
+    size: u64,
+}
+
+/// Logfiles in `dir` named `<prefix>_<date>_<index>.jsonl`, plain or compressed, from the newest to
+/// the oldest one. Logfiles of other monitors sharing the directory are left alone, even if their
+/// prefix starts with `prefix`.
+fn collect_logfiles(dir: &Path, prefix: &str) -> Result<Vec<Logfile>> {
+    let mut logfiles = read_dir(dir)
+        .with_context(|| format!("Failed to read log directory '{}'", dir.display()))?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| archive::is_logfile(&entry.path()))
+        .filter_map(|entry| {
+            let path = entry.path();
+            let (name_prefix, date, index) = parse_logfile_name(path.file_name()?.to_str()?)?;
+            if name_prefix != prefix {
+                return None;
+            }
+            let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+            let size = entry.metadata().ok()?.len();
+
+            Some(Logfile {
+                path,
+                date,
+                index,
+                size,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    logfiles.sort_by_key(|logfile| (logfile.date, logfile.index));
+    logfiles.reverse();
+
+    Ok(logfiles)
+}
+
+fn count_per_day<'a>(logfiles: impl Iterator<Item = &'a Logfile>) -> HashMap<NaiveDate, usize> {
+    logfiles.fold(HashMap::new(), |mut days, logfile| {
+        *days.entry(logfile.date).or_default() += 1;
+        days
+    })
+}
+
+/// Picks the logfiles to remove from the newest to the oldest ones.
+fn select(
+    logfiles: Vec<Logfile>,
+    retention: &RetentionConfig,
+    today: NaiveDate,
+) -> Vec<(Logfile, PruneReason)> {
+    let mut kept_files = 0;
+    let mut kept_size = 0;
+    let mut selected = Vec::new();
+
+    for logfile in logfiles {
+        let too_old = retention
+            .max_age
+            .is_some_and(|max_age| (today - logfile.date).num_days() > max_age as i64);
+        let too_many = retention
+            .max_files
+            .is_some_and(|max_files| kept_files >= max_files);
+        let too_large = retention
+            .max_size
+            .is_some_and(|max_size| kept_size + logfile.size > max_size);
+        let reason = match (kept_files, too_old, too_many, too_large) {
+            (0, ..) => None,
+            (_, true, ..) => Some(PruneReason::Age),
+            (_, _, true, _) => Some(PruneReason::Count),
+            (_, _, _, true) => Some(PruneReason::Size),
+            _ => None,
+        };
+
+        match reason {
+            Some(reason) => selected.push((logfile, reason)),
+            None => {
+                kept_files += 1;
+                kept_size += logfile.size;
+            }
+        }
+    }
+
+    selected.reverse();
+    selected
+}
+
+/// Outages and uptime of one day, which are kept after its logfiles were removed.
+#[derive(Serialize)]
+struct DaySummary {
+    timestamp: DateTime<Utc>,
+    date: NaiveDate,
+    logfiles: Vec<String>,
+    checks: usize,
+    observed_secs: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uptime: Option<f64>,
+    degraded_secs: i64,
+    offline_secs: i64,
+    not_running_secs: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    latency_avg_ms: Option<u128>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    latency_p95_ms: Option<u128>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    latency_max_ms: Option<u128>,
+    outages: Vec<OutageSummary>,
+}
+
+#[derive(Serialize)]
+struct OutageSummary {
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    duration_secs: i64,
+}
+
+/// Days the summary file in the log directory holds a summary for.
+fn summarized_days(dir: &Path) -> Result<HashSet<NaiveDate>> {
+    #[derive(Deserialize)]
+    struct SummarizedDay {
+        date: NaiveDate,
+    }
+
+    let path = dir.join(SUMMARY_DIR).join(SUMMARY_FILE);
+    let file = match File::open(&path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(HashSet::new()),
+        Err(err) => {
+            return Err(err).with_context(|| format!("Failed to read '{}'", path.display()));
+        }
+    };
+
+    Ok(BufReader::new(file)
+        .lines()
+        .map_while(|line| line.ok())
+        .filter_map(|line| serde_json::from_str::<SummarizedDay>(&line).ok())
+        .map(|summary| summary.date)
+        .collect())
+}
+
+/// Appends the summary of one day to the summary file in the log directory.
+fn write_summary(dir: &Path, date: NaiveDate, paths: Vec<PathBuf>, config: &Config) -> Result<()> {
+    let logfiles = paths
+        .iter()
+        .filter_map(|path| path.file_name()?.to_str().map(String::from))
+        .collect();
+    let report = Report::from_path_bufs(paths, config.report.log_precision())
+        .with_debounce(config.debounce.clone())
+        .with_gap_threshold(config.report.gap_threshold);
+    let stats = report.stats();
+
+    let summary = DaySummary {
+        timestamp: Utc::now(),
+        date,
+        logfiles,
+        checks: stats.checks(),
+        observed_secs: stats.observed().num_seconds(),
+        uptime: stats.uptime(),
+        degraded_secs: stats.degraded().num_seconds(),
+        offline_secs: stats.offline().num_seconds(),
+        not_running_secs: stats.not_running().num_seconds(),
+        latency_avg_ms: stats.latency_avg().map(|avg| avg.as_millis()),
+        latency_p95_ms: stats.latency_percentile(0.95).map(|p95| p95.as_millis()),
+        latency_max_ms: stats.latency_max().map(|max| max.as_millis()),
+        outages: report
+            .all_outages()
+            .iter()
+            .map(|outage| OutageSummary {
+                start: *outage.start(),
+                end: *outage.end(),
+                duration_secs: outage.duration().num_seconds(),
+            })
+            .collect(),
+    };
+
+    let summary_dir = dir.join(SUMMARY_DIR);
+    create_dir_all(&summary_dir)?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(summary_dir.join(SUMMARY_FILE))?;
+    let mut buffer = serde_json::to_vec(&summary)?;
+    buffer.push(b'\n');
+    file.write_all(&buffer)?;
+    file.sync_all()?;
+
+    Ok(())
+}
+
+pub fn format_size(size: u64) -> String {
+    match size {
+        0..1024 => format!("{size} B"),
+        1024..1_048_576 => format!("{:.1} KiB", size as f64 / 1024.0),
+        _ => format!("{:.1} MiB", size as f64 / 1_048_576.0),
+    }
+}
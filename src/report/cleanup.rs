@@ -1,24 +1,117 @@
-use std::path::PathBuf;
-
-use chrono::Utc;
-
-use crate::report::Report;
-
-pub fn handle(report: Report) {
-    report.iter_logfile_paths().for_each(|p: &PathBuf| {
-        if let Some(str) = p.to_str() {
-            let msg = match std::fs::remove_file(p) {
-                Ok(_) => format!("[{}] - Success: removed file '{}'", timestamp(), str),
-                Err(err) => format!(
-                    "[{}] - Error:   removed file '{}' | {}",
-                    timestamp(),
-                    str,
-                    err
-                ),
-            };
-            println!("{msg}");
+use std::{io::IsTerminal, path::PathBuf, time::SystemTime};
+
+use anyhow::Result;
+use chrono::{DateTime, Local, Utc};
+use inquire::Confirm;
+
+use crate::{app::report::CleanupArgs, archive, proc, prune::format_size, report::Report};
+
+/// Removes the selected logfiles after listing and confirming them. Logfiles a process still
+/// holds open are never removed, and neither is the newest logfile of each prefix, since a
+/// monitor the open files can't be checked for may still write into it.
+pub fn handle(report: Report, args: &CleanupArgs) -> Result<()> {
+    let now = SystemTime::now();
+    let newest = archive::newest_in_dirs(&report.iter_logfile_paths().cloned().collect::<Vec<_>>());
+    let mut warned = false;
+    let mut logfiles = Vec::new();
+
+    for path in report.iter_logfile_paths() {
+        let metadata = match path.metadata() {
+            Ok(metadata) => metadata,
+            Err(err) => {
+                println!("Skipping '{}': {err}", path.display());
+                continue;
+            }
+        };
+        let modified = metadata.modified().unwrap_or(now);
+
+        if let Some(older_than) = args.older_than
+            && now.duration_since(modified).unwrap_or_default() < older_than
+        {
+            continue;
+        }
+        if archive::is_newest(path, &newest) {
+            println!(
+                "Skipping '{}': newest logfile, a monitor may still write into it",
+                path.display()
+            );
+            continue;
+        }
+        match proc::open_by(path) {
+            Some(processes) => {
+                if let Some((pid, name)) = processes.first() {
+                    println!(
+                        "Skipping '{}': still open by process {pid} ({name})",
+                        path.display()
+                    );
+                    continue;
+                }
+            }
+            None if !warned => {
+                eprintln!("Warning: Can't check which logfiles are still open on this system");
+                warned = true;
+            }
+            None => {}
+        }
+
+        logfiles.push((path.clone(), metadata.len(), modified));
+    }
+
+    if logfiles.is_empty() {
+        println!("No logfiles to remove");
+        return Ok(());
+    }
+
+    for (path, size, modified) in &logfiles {
+        println!(
+            "  {}  {}  last written {}",
+            path.display(),
+            format_size(*size),
+            DateTime::<Local>::from(*modified).format("%Y-%m-%d %H:%M")
+        );
+    }
+    let total = format_size(logfiles.iter().map(|(_, size, _)| size).sum());
+
+    if args.dry_run {
+        println!("Would remove {} logfiles ({total})", logfiles.len());
+        return Ok(());
+    }
+    if !args.yes {
+        if !std::io::stdin().is_terminal() {
+            return Err(anyhow::anyhow!(
+                "Refusing to remove logfiles without confirmation, pass --yes"
+            ));
+        }
+
+        let confirmed = Confirm::new(&format!("Remove {} logfiles ({total})?", logfiles.len()))
+            .with_default(false)
+            .prompt()?;
+        if !confirmed {
+            println!("Nothing removed");
+            return Ok(());
         }
-    });
+    }
+
+    for (path, _, _) in &logfiles {
+        remove(path);
+    }
+
+    Ok(())
+}
+
+fn remove(p: &PathBuf) {
+    if let Some(str) = p.to_str() {
+        let msg = match std::fs::remove_file(p) {
+            Ok(_) => format!("[{}] - Success: removed file '{}'", timestamp(), str),
+            Err(err) => format!(
+                "[{}] - Error:   removed file '{}' | {}",
+                timestamp(),
+                str,
+                err
+            ),
+        };
+        println!("{msg}");
+    }
 }
 
 fn timestamp() -> String {
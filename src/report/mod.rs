@@ -2,21 +2,50 @@ use anyhow::Result;
 
 use crate::{
     app::report::{ReportArgs, ReportMode},
+    config::Config,
+    database::Database,
     model::Report,
     project::Project,
 };
 
 mod cleanup;
 mod outages;
+mod phases;
 mod simple;
+mod slow;
+mod uptime;
 
-pub async fn run(args: ReportArgs, project: Project) -> Result<()> {
-    let report = Report::from_path_bufs(args.logfiles(&project)?, args.log_precision());
+pub async fn run(args: ReportArgs, config: Config, project: Project) -> Result<()> {
+    let database = config
+        .database(&project)
+        .filter(|_| args.reads_database(&config.report.mode));
+    let report = match database {
+        Some(path) => {
+            let database = Database::open(&path)?;
+            Report::from_database(
+                &database,
+                &args.days(&database)?,
+                config.report.log_precision(),
+            )?
+        }
+        None => Report::from_path_bufs(
+            args.logfiles(config.log_dir(&project), &config.report.mode)?,
+            config.report.log_precision(),
+        ),
+    }
+    .with_debounce(config.debounce.clone())
+    .with_gap_threshold(config.report.gap_threshold);
+    if report.unreadable() > 0 {
+        eprintln!("Skipped {} unreadable log entries", report.unreadable());
+    }
 
-    match args.mode {
+    match config.report.mode {
         ReportMode::Simple => simple::handle(report),
         ReportMode::Outages => outages::handle(report),
-        ReportMode::Cleanup => cleanup::handle(report),
+        ReportMode::Phases => phases::handle(report),
+        ReportMode::Slow => slow::handle(report, config.report.latency_threshold),
+        ReportMode::Uptime => uptime::handle(report),
+        ReportMode::Cleanup => cleanup::handle(report, &args.cleanup)?,
     }
 
     Ok(())
@@ -1,53 +1,112 @@
-use std::borrow::Borrow;
-
 use chrono::{DateTime, Local, TimeDelta, Utc};
 
 use crate::{
-    model::{InternetCheckResult, OutageLogPrecision, Report, ReportItem},
-    time::Humanize,
+    config::DebounceConfig,
+    model::{
+        Connectivity, InternetCheckResult, MonitorGap, Outage, OutageLogPrecision, Report,
+        ReportItem,
+    },
+    time::{Humanize, calculate_avg},
     tracker::DowntimeTracker,
 };
 
 pub fn handle(report: Report) {
     handle_report(report.clone());
 
-    let mut tracker = DurationTracker::new();
+    let mut tracker = DurationTracker::for_outages(report.debounce(), report.gaps());
 
     let deltas = report
         .iter_all_results()
-        .filter_map(|r| tracker.track(r).and_then(|(d, _, _)| Some(d)))
+        .filter_map(|r| tracker.track(r).map(|(d, _, _)| d))
         .collect::<Vec<TimeDelta>>();
 
     println!("Outages: {}", deltas.len());
 
-    if let Some(avg) = DurationTracker::calculate_avg(&deltas) {
+    if let Some(avg) = calculate_avg(&deltas) {
         println!("Average duration: {}", avg.humanize());
     }
 
+    let mut degraded_tracker = DurationTracker::for_state(Connectivity::Degraded, report.gaps());
+    let degraded_deltas = report
+        .iter_all_results()
+        .filter_map(|r| degraded_tracker.track(r).map(|(d, _, _)| d))
+        .collect::<Vec<TimeDelta>>();
+
+    if !degraded_deltas.is_empty() {
+        println!("Degraded periods: {}", degraded_deltas.len());
+
+        if let Some(avg) = calculate_avg(&degraded_deltas) {
+            println!("Average degraded duration: {}", avg.humanize());
+        }
+    }
+
+    let gaps = report.gaps();
+    if !gaps.is_empty() {
+        println!("Monitor not running: {}", gaps.len());
+
+        if let Some(avg) = calculate_avg(gaps.iter().map(|g| g.duration())) {
+            println!("Average duration not running: {}", avg.humanize());
+        }
+    }
+
     handle_still_outage(tracker);
 }
 
 fn handle_report(report: Report) {
     report
         .iter_items()
-        .map(|i| (i, report.log_precision()))
-        .for_each(handle_report_item);
+        .for_each(|i| handle_report_item(i, report.debounce(), report.log_precision()));
 }
 
-fn handle_report_item(data: (&ReportItem, OutageLogPrecision)) {
-    let (item, log_precision) = data;
-    let outages = item.outages(log_precision);
+fn handle_report_item(
+    item: &ReportItem,
+    debounce: &DebounceConfig,
+    log_precision: OutageLogPrecision,
+) {
+    let outages = item.outages(debounce, log_precision);
 
     println!("Duration Report for: {}", item.logfile_name());
-    outages.iter().for_each(|outage| println!("{outage}"));
+    outages.iter().for_each(print_outage);
 
-    if let Some(avg) = DurationTracker::calculate_avg(outages.iter().map(|o| o.duration())) {
+    if let Some(avg) = calculate_avg(outages.iter().map(|o| o.duration())) {
         println!("Average duration: {}", avg.humanize());
     }
 
+    let degradations = item.degradations(log_precision);
+    if !degradations.is_empty() {
+        println!();
+        println!("Degraded periods:");
+        degradations.iter().for_each(print_outage);
+
+        if let Some(avg) = calculate_avg(degradations.iter().map(|o| o.duration())) {
+            println!("Average degraded duration: {}", avg.humanize());
+        }
+    }
+
+    if !item.gaps().is_empty() {
+        println!();
+        println!("Monitor not running:");
+        item.gaps().iter().for_each(|gap| println!("{gap}"));
+    }
+
     println!();
 }
 
+fn print_outage(outage: &Outage) {
+    println!("{outage}");
+
+    let failed = outage
+        .iter_failed_targets()
+        .map(|r| match r.error() {
+            Some(error) => format!("{} ({}: {})", r.target(), r.probe(), error),
+            None => format!("{} ({})", r.target(), r.probe()),
+        })
+        .collect::<Vec<String>>();
+    if !failed.is_empty() {
+        println!("  Failed: {}", failed.join(", "));
+    }
+}
+
 fn handle_still_outage(tracker: DurationTracker) {
     if let Some((time, elapsed)) = tracker.still_outage() {
         let message_lost = format!("Connection lost since: {time}");
@@ -79,8 +138,12 @@ fn handle_still_outage(tracker: DurationTracker) {
 struct DurationTracker<'a>(DowntimeTracker<'a>);
 
 impl<'a> DurationTracker<'a> {
-    fn new() -> Self {
-        Self(DowntimeTracker::new())
+    fn for_outages(debounce: &DebounceConfig, gaps: &[MonitorGap]) -> Self {
+        Self(DowntimeTracker::for_outages(debounce).with_gaps(gaps.to_vec()))
+    }
+
+    fn for_state(state: Connectivity, gaps: &[MonitorGap]) -> Self {
+        Self(DowntimeTracker::for_state(state).with_gaps(gaps.to_vec()))
     }
 
     fn track(
@@ -113,26 +176,4 @@ impl<'a> DurationTracker<'a> {
         let since = Utc::now() - timestamp;
         since.humanize()
     }
-
-    fn calculate_avg<I>(deltas: I) -> Option<TimeDelta>
-    where
-        I: IntoIterator,
-        I::Item: Borrow<TimeDelta>,
-        I::IntoIter: ExactSizeIterator,
-    {
-        let iter = deltas.into_iter();
-        let count = iter.len();
-
-        if count == 0 {
-            return None;
-        }
-
-        let total_nanos: i128 = iter
-            .map(|d| d.borrow().num_nanoseconds().unwrap_or(0) as i128)
-            .sum();
-
-        let avg_nanos = total_nanos / (count as i128);
-
-        Some(TimeDelta::nanoseconds(avg_nanos as i64))
-    }
 }
@@ -0,0 +1,101 @@
+use std::{fmt::Display, time::Duration};
+
+use crate::model::{Report, TargetResult};
+
+pub fn handle(report: Report) {
+    for item in report.iter_items() {
+        println!("Latency phases for: {}", item.logfile_name());
+
+        let mut stats: Vec<TargetPhaseStats> = vec![];
+        item.iter_results()
+            .flat_map(|result| result.iter_target_results())
+            .for_each(
+                |result| match stats.iter_mut().find(|s| s.target == result.target()) {
+                    Some(entry) => entry.add(result),
+                    None => stats.push(TargetPhaseStats::from_result(result)),
+                },
+            );
+
+        println!(
+            "  {:<16} {:>8} {:>10} {:>10} {:>10} {:>10} {:>10}",
+            "Target", "Samples", "DNS", "Connect", "TLS", "1st Byte", "Total"
+        );
+        stats.iter().for_each(|s| {
+            println!(
+                "  {:<16} {:>8} {:>10} {:>10} {:>10} {:>10} {:>10}",
+                s.target,
+                s.total.count,
+                s.dns.to_string(),
+                s.connect.to_string(),
+                s.tls.to_string(),
+                s.first_byte.to_string(),
+                s.total.to_string(),
+            )
+        });
+        println!();
+    }
+}
+
+struct TargetPhaseStats<'a> {
+    target: &'a str,
+    dns: Mean,
+    connect: Mean,
+    tls: Mean,
+    first_byte: Mean,
+    total: Mean,
+}
+
+impl<'a> TargetPhaseStats<'a> {
+    fn from_result(result: &'a TargetResult) -> Self {
+        let mut stats = Self {
+            target: result.target(),
+            dns: Mean::default(),
+            connect: Mean::default(),
+            tls: Mean::default(),
+            first_byte: Mean::default(),
+            total: Mean::default(),
+        };
+        stats.add(result);
+
+        stats
+    }
+
+    fn add(&mut self, result: &TargetResult) {
+        let phases = result.latency().phases().cloned().unwrap_or_default();
+
+        self.dns.add(phases.dns);
+        self.connect.add(phases.connect);
+        self.tls.add(phases.tls);
+        self.first_byte.add(phases.first_byte);
+        self.total.add(Some(*result.latency_duration()));
+    }
+}
+
+#[derive(Default)]
+struct Mean {
+    sum: Duration,
+    count: u32,
+}
+
+impl Mean {
+    fn add(&mut self, duration: Option<Duration>) {
+        if let Some(duration) = duration {
+            self.sum += duration;
+            self.count += 1;
+        }
+    }
+}
+
+impl Display for Mean {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.count == 0 {
+            return write!(f, "-");
+        }
+
+        write!(
+            f,
+            "{:.1} ms",
+            (self.sum / self.count).as_secs_f64() * 1000.0
+        )
+    }
+}
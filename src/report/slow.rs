@@ -0,0 +1,33 @@
+use crate::{
+    model::{DEFAULT_LATENCY_THRESHOLD, Report},
+    time::{Humanize, calculate_avg},
+};
+
+pub fn handle(report: Report, latency_threshold: Option<u64>) {
+    let log_precision = report.log_precision();
+
+    for item in report.iter_items() {
+        let periods = item.slow_periods(latency_threshold, log_precision);
+
+        println!("Slow Report for: {}", item.logfile_name());
+        periods.iter().for_each(|p| println!("{p}"));
+
+        if let Some(avg) = calculate_avg(periods.iter().map(|p| p.duration())) {
+            println!("Average duration: {}", avg.humanize());
+        }
+
+        println!();
+    }
+
+    let periods = report.all_slow_periods(latency_threshold);
+    println!("Slow periods: {}", periods.len());
+
+    if let Some(avg) = calculate_avg(periods.iter().map(|p| p.duration())) {
+        println!("Average duration: {}", avg.humanize());
+    }
+
+    match latency_threshold {
+        Some(threshold) => println!("Latency threshold: {threshold} ms"),
+        None => println!("Latency threshold: as logged (default {DEFAULT_LATENCY_THRESHOLD} ms)"),
+    }
+}
@@ -0,0 +1,65 @@
+use std::time::Duration;
+
+use crate::{
+    model::{CheckStats, Report},
+    time::Humanize,
+};
+
+pub fn handle(report: Report) {
+    for item in report.iter_items() {
+        println!("Uptime Report for: {}", item.logfile_name());
+        print_stats(&item.stats());
+        println!();
+    }
+
+    println!("Total:");
+    print_stats(&report.stats());
+}
+
+fn print_stats(stats: &CheckStats) {
+    println!("  Checks: {}", stats.checks());
+    println!("  Observed: {}", stats.observed().humanize());
+
+    match stats.uptime() {
+        Some(uptime) => println!("  Uptime: {}", percent(uptime)),
+        None => println!("  Uptime: -"),
+    }
+    if let Some(share) = stats.degraded_share().filter(|share| *share > 0.0) {
+        println!(
+            "  Degraded: {} ({})",
+            percent(share),
+            stats.degraded().humanize()
+        );
+    }
+    if stats.offline().num_seconds() > 0 {
+        println!("  Offline: {}", stats.offline().humanize());
+    }
+    if stats.not_running().num_seconds() > 0 {
+        println!("  Monitor not running: {}", stats.not_running().humanize());
+    }
+
+    if let (Some(avg), Some(min), Some(max)) = (
+        stats.latency_avg(),
+        stats.latency_min(),
+        stats.latency_max(),
+    ) {
+        println!(
+            "  Latency: avg {}, min {}, p50 {}, p95 {}, p99 {}, max {} ({} samples)",
+            millis(avg),
+            millis(min),
+            millis(stats.latency_percentile(0.5).unwrap_or(avg)),
+            millis(stats.latency_percentile(0.95).unwrap_or(max)),
+            millis(stats.latency_percentile(0.99).unwrap_or(max)),
+            millis(max),
+            stats.latency_samples(),
+        );
+    }
+}
+
+fn percent(share: f64) -> String {
+    format!("{:.3} %", share * 100.0)
+}
+
+fn millis(duration: Duration) -> String {
+    format!("{} ms", duration.as_millis())
+}
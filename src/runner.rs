@@ -4,49 +4,93 @@ use std::{
 };
 
 use anyhow::Result;
-use reqwest::Client;
 
-use crate::{log::Logger, model::Connectivity};
+use crate::{log::Logger, systemd::Watchdog};
 
-pub async fn run_loop<Cb, FutCb, Shutdown, FutShutdown>(
-    client: Client,
+/// Calls back until CTRL-C is pressed or SIGTERM is received. The state returned by the callback
+/// is handed to the next call and decides about the interval until then. On SIGHUP, the reload
+/// callback is called without interrupting the interval, and its errors are only reported. If
+/// systemd watches the process, it is pinged as long as the callbacks keep up with the interval.
+pub async fn run_loop<State, Interval, Cb, FutCb, Shutdown, FutShutdown, Reload, FutReload>(
     logger: Arc<Logger>,
-    duration: Duration,
+    interval: Interval,
     cb: Cb,
     shutdown: Option<Shutdown>,
+    reload: Option<Reload>,
 ) -> Result<()>
 where
-    Cb: Fn(Client, Arc<Logger>, Option<Connectivity>) -> FutCb,
-    FutCb: Future<Output = Result<Connectivity>>,
+    State: Copy,
+    Interval: Fn(&State) -> Duration,
+    Cb: Fn(Arc<Logger>, Option<State>) -> FutCb,
+    FutCb: Future<Output = Result<State>>,
     Shutdown: FnOnce() -> FutShutdown,
     FutShutdown: Future<Output = Result<()>>,
+    Reload: Fn() -> FutReload,
+    FutReload: Future<Output = Result<()>>,
 {
-    println!("Press CTRL-C to abort...");
+    let mut shutdown_signal = ShutdownSignal::new()?;
+    let mut reload_signal = ReloadSignal::new()?;
+    let mut watchdog = Watchdog::from_env();
 
-    let mut previous = None::<Connectivity>;
-    let start = Instant::now();
+    logger.print("Press CTRL-C to abort...");
+
+    // The pending tick survives a reload, so it neither restarts the interval nor cancels a
+    // running callback.
+    let next_tick =
+        |start: Instant, state: &State| tokio::time::Instant::from_std(start) + interval(state);
+    let schedule = |next_tick: tokio::time::Instant, state: State| {
+        let logger = Arc::clone(&logger);
+        let cb = &cb;
+
+        Box::pin(async move {
+            tokio::time::sleep_until(next_tick).await;
+
+            let start = Instant::now();
+            let result = cb(logger, Some(state)).await;
 
-    previous = Some(cb(client.clone(), Arc::clone(&logger), previous).await?);
-    let mut next_tick = duration.saturating_sub(start.elapsed());
+            (result, start)
+        })
+    };
+
+    let start = Instant::now();
+    let state = cb(Arc::clone(&logger), None).await?;
+    let mut deadline = next_tick(start, &state);
+    let mut tick = schedule(deadline, state);
 
     loop {
         tokio::select! {
-            res = async {
-                tokio::time::sleep(next_tick).await;
-
-                let start = Instant::now();
-                let result = cb(client.clone(), Arc::clone(&logger), previous).await;
-                let next = duration.saturating_sub(start.elapsed());
+            (cb_result, start) = &mut tick => {
+                let state = cb_result?;
+                deadline = next_tick(start, &state);
+                tick = schedule(deadline, state);
+            }
 
-                (result, next)
+            _ = async {
+                match watchdog.as_mut() {
+                    Some(watchdog) => watchdog.tick().await,
+                    None => std::future::pending().await,
+                }
             } => {
-                let (cb_result, new_tick) = res;
-                previous = Some(cb_result?);
-                next_tick = new_tick;
+                // A callback that hangs for longer than the watchdog timeout stops the pings.
+                if let Some(watchdog) = &watchdog
+                    && tokio::time::Instant::now() <= deadline + watchdog.timeout()
+                    && let Err(err) = watchdog.ping()
+                {
+                    log!(logger, "Watchdog ping failed", error = format!("{err:#}"))?;
+                }
+            }
+
+            _ = reload_signal.recv() => {
+                // The current config stays in place, so a failed reload never stops the checks.
+                if let Some(reload_cb) = &reload
+                    && let Err(err) = reload_cb().await
+                {
+                    logger.print_error(&format!("Config reload failed: {err:#}"));
+                }
             }
 
-            _ = tokio::signal::ctrl_c() => {
-                println!("Gracefully shutdown...");
+            _ = shutdown_signal.recv() => {
+                logger.print("Gracefully shutdown...");
 
                 if let Some(shutdown_cb) = shutdown {
                     shutdown_cb().await?;
@@ -59,3 +103,52 @@ where
 
     Ok(())
 }
+
+/// CTRL-C and, on Unix, SIGTERM, e.g. from systemd or Docker.
+struct ShutdownSignal {
+    #[cfg(unix)]
+    terminate: tokio::signal::unix::Signal,
+}
+
+impl ShutdownSignal {
+    fn new() -> Result<Self> {
+        Ok(Self {
+            #[cfg(unix)]
+            terminate: tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?,
+        })
+    }
+
+    async fn recv(&mut self) {
+        #[cfg(unix)]
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {},
+            _ = self.terminate.recv() => {},
+        }
+
+        #[cfg(not(unix))]
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
+
+/// SIGHUP on Unix. Never received on other platforms.
+struct ReloadSignal {
+    #[cfg(unix)]
+    hangup: tokio::signal::unix::Signal,
+}
+
+impl ReloadSignal {
+    fn new() -> Result<Self> {
+        Ok(Self {
+            #[cfg(unix)]
+            hangup: tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())?,
+        })
+    }
+
+    async fn recv(&mut self) {
+        #[cfg(unix)]
+        self.hangup.recv().await;
+
+        #[cfg(not(unix))]
+        std::future::pending::<()>().await;
+    }
+}
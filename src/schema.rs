@@ -0,0 +1,61 @@
+use anyhow::Result;
+use serde_json::{Value, json};
+
+/// Version of the layout of the log entries, written into every entry as `schema_version`.
+///
+/// 1. Entries without a version, written before it was introduced. Target results of HTTP checks
+///    may lack `probe`, which then defaults to `Http`.
+/// 2. Every target result has its `probe`.
+///
+/// Whenever the serialized layout of an entry changes, the version is increased and a migration
+/// step from the previous version is added to [`migrate`].
+pub const SCHEMA_VERSION: u64 = 2;
+
+/// Version of an entry. Entries without one were written before versioning.
+pub fn version(entry: &Value) -> u64 {
+    entry
+        .get("schema_version")
+        .and_then(Value::as_u64)
+        .unwrap_or(1)
+}
+
+/// Brings an entry of any known version to the current layout. Entries of newer versions, e.g.
+/// written by a newer release, can't be read.
+pub fn migrate(mut entry: Value) -> Result<Value> {
+    let version = version(&entry);
+    if version > SCHEMA_VERSION {
+        return Err(anyhow::anyhow!(
+            "Schema version {version} is newer than the supported version {SCHEMA_VERSION}"
+        ));
+    }
+
+    if version < 2 {
+        entry = migrate_v1(entry);
+    }
+
+    if let Value::Object(fields) = &mut entry {
+        fields.insert("schema_version".into(), json!(SCHEMA_VERSION));
+    }
+
+    Ok(entry)
+}
+
+/// Adds the probe, which HTTP results written before TCP and DNS probes existed lack. The monitor
+/// itself reads them as `Http` anyway, but migrated logfiles and the entries stored in the
+/// database are queried by other tools as well, which should find the probe in every result.
+fn migrate_v1(mut entry: Value) -> Value {
+    let results = entry
+        .get_mut("result")
+        .and_then(|result| result.get_mut("results"))
+        .and_then(Value::as_array_mut);
+
+    for result in results.into_iter().flatten() {
+        if let Value::Object(fields) = result
+            && !fields.contains_key("probe")
+        {
+            fields.insert("probe".into(), json!("Http"));
+        }
+    }
+
+    entry
+}
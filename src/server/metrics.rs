@@ -0,0 +1,169 @@
+use std::fmt::{Display, Write};
+
+use anyhow::Result;
+use chrono::Utc;
+
+use crate::{
+    model::Connectivity,
+    server::Response,
+    status::{LATENCY_BUCKETS, MonitorStatus},
+};
+
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Renders the status in the Prometheus text format.
+pub fn render(status: &MonitorStatus) -> Result<Response> {
+    let data = status.lock()?;
+    let mut out = String::new();
+
+    metric(
+        &mut out,
+        "netcheck_connectivity",
+        "gauge",
+        "Connectivity confirmed by the monitor, 1 for the current state.",
+    );
+    for (state, connectivity) in [
+        ("online", Connectivity::Online),
+        ("degraded", Connectivity::Degraded),
+        ("offline", Connectivity::Offline),
+    ] {
+        let value = u8::from(data.connectivity == Some(connectivity));
+        sample(
+            &mut out,
+            "netcheck_connectivity",
+            &[("state", state)],
+            value,
+        );
+    }
+
+    metric(
+        &mut out,
+        "netcheck_outages_total",
+        "counter",
+        "Outages since the monitor started.",
+    );
+    sample(&mut out, "netcheck_outages_total", &[], data.outages);
+
+    let outage_duration = match (data.connectivity, data.since) {
+        (Some(Connectivity::Offline), Some(since)) => (Utc::now() - since).num_milliseconds(),
+        _ => 0,
+    };
+    metric(
+        &mut out,
+        "netcheck_outage_duration_seconds",
+        "gauge",
+        "Duration of the current outage, 0 while the connection is up.",
+    );
+    sample(
+        &mut out,
+        "netcheck_outage_duration_seconds",
+        &[],
+        outage_duration as f64 / 1000.0,
+    );
+
+    metric(
+        &mut out,
+        "netcheck_target_up",
+        "gauge",
+        "Whether the last check of the target succeeded.",
+    );
+    for (target, stats) in &data.targets {
+        let labels = [("target", target.as_str())];
+        sample(&mut out, "netcheck_target_up", &labels, u8::from(stats.up));
+    }
+
+    metric(
+        &mut out,
+        "netcheck_target_checks_total",
+        "counter",
+        "Checks of the target by result.",
+    );
+    for (target, stats) in &data.targets {
+        for (result, count) in [("success", stats.successes), ("failure", stats.failures)] {
+            let labels = [("target", target.as_str()), ("result", result)];
+            sample(&mut out, "netcheck_target_checks_total", &labels, count);
+        }
+    }
+
+    metric(
+        &mut out,
+        "netcheck_check_errors_total",
+        "counter",
+        "Failed checks of the target by error kind.",
+    );
+    for (target, stats) in &data.targets {
+        for (kind, count) in &stats.errors {
+            let labels = [("target", target.as_str()), ("kind", kind)];
+            sample(&mut out, "netcheck_check_errors_total", &labels, count);
+        }
+    }
+
+    metric(
+        &mut out,
+        "netcheck_target_latency_seconds",
+        "histogram",
+        "Latency of the successful checks of the target.",
+    );
+    for (target, stats) in &data.targets {
+        let histogram = &stats.latency;
+
+        for (bound, count) in LATENCY_BUCKETS.iter().zip(histogram.buckets) {
+            let bound = bound.to_string();
+            let labels = [("target", target.as_str()), ("le", bound.as_str())];
+            sample(
+                &mut out,
+                "netcheck_target_latency_seconds_bucket",
+                &labels,
+                count,
+            );
+        }
+
+        let labels = [("target", target.as_str()), ("le", "+Inf")];
+        sample(
+            &mut out,
+            "netcheck_target_latency_seconds_bucket",
+            &labels,
+            histogram.count,
+        );
+
+        let labels = [("target", target.as_str())];
+        sample(
+            &mut out,
+            "netcheck_target_latency_seconds_sum",
+            &labels,
+            histogram.sum,
+        );
+        sample(
+            &mut out,
+            "netcheck_target_latency_seconds_count",
+            &labels,
+            histogram.count,
+        );
+    }
+
+    Ok(Response::ok(CONTENT_TYPE, out))
+}
+
+fn metric(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+fn sample(out: &mut String, name: &str, labels: &[(&str, &str)], value: impl Display) {
+    let labels = labels
+        .iter()
+        .map(|(key, value)| format!("{key}=\"{}\"", escape(value)))
+        .collect::<Vec<String>>();
+
+    let _ = match labels.is_empty() {
+        true => writeln!(out, "{name} {value}"),
+        false => writeln!(out, "{name}{{{}}} {value}", labels.join(",")),
+    };
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
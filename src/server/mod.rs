@@ -0,0 +1,232 @@
+use std::{
+    net::SocketAddr,
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader},
+    net::TcpListener,
+    time::timeout as with_timeout,
+};
+
+#[cfg(unix)]
+use tokio::net::UnixListener;
+
+use crate::{log::Logger, status::MonitorStatus};
+
+mod metrics;
+mod status;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_REQUEST_LINES: usize = 100;
+/// Wait after a failed accept, doubled while it keeps failing, e.g. without free file descriptors.
+const ACCEPT_RETRY_MIN: Duration = Duration::from_millis(100);
+const ACCEPT_RETRY_MAX: Duration = Duration::from_secs(5);
+const ACCEPT_LOG_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Answers a GET request for a path, e.g. `/metrics`.
+pub type Route = fn(&MonitorStatus) -> Result<Response>;
+
+pub struct Response {
+    status: u16,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Response {
+    pub fn ok(content_type: &'static str, body: String) -> Self {
+        Self {
+            status: 200,
+            content_type,
+            body,
+        }
+    }
+
+    fn error(status: u16) -> Self {
+        Self {
+            status,
+            content_type: "text/plain; charset=utf-8",
+            body: format!("{}\n", reason(status)),
+        }
+    }
+}
+
+/// Binds the metrics endpoint. Binding happens up front, so a taken port fails the start of the
+/// monitor instead of going unnoticed.
+pub async fn bind_metrics(addr: SocketAddr) -> Result<Server> {
+    Server::bind(addr, METRICS_ROUTES).await
+}
+
+/// Binds the status endpoint on a TCP address.
+pub async fn bind_status(addr: SocketAddr) -> Result<Server> {
+    Server::bind(addr, STATUS_ROUTES).await
+}
+
+/// Binds the status endpoint on a Unix socket. A socket left behind by an earlier run is replaced.
+#[cfg(unix)]
+pub fn bind_status_socket(path: &Path) -> Result<Server> {
+    use std::os::unix::fs::FileTypeExt;
+
+    if std::fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_socket()) {
+        std::fs::remove_file(path)
+            .with_context(|| format!("Failed to remove socket '{}'", path.display()))?;
+    }
+    let listener = UnixListener::bind(path)
+        .with_context(|| format!("Failed to listen on '{}'", path.display()))?;
+
+    Ok(Server {
+        listener: Listener::Unix(listener),
+        routes: STATUS_ROUTES,
+    })
+}
+
+#[cfg(not(unix))]
+pub fn bind_status_socket(path: &Path) -> Result<Server> {
+    anyhow::bail!(
+        "Cannot listen on '{}', Unix sockets are not supported on this platform",
+        path.display()
+    )
+}
+
+const METRICS_ROUTES: &[(&str, Route)] = &[("/metrics", metrics::render)];
+const STATUS_ROUTES: &[(&str, Route)] = &[("/status", status::render)];
+
+pub struct Server {
+    listener: Listener,
+    routes: &'static [(&'static str, Route)],
+}
+
+enum Listener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(UnixListener),
+}
+
+impl Server {
+    async fn bind(addr: SocketAddr, routes: &'static [(&'static str, Route)]) -> Result<Self> {
+        let listener = TcpListener::bind(addr)
+            .await
+            .with_context(|| format!("Failed to listen on '{addr}'"))?;
+
+        Ok(Self {
+            listener: Listener::Tcp(listener),
+            routes,
+        })
+    }
+
+    /// Serves the routes in the background, so requests never delay the checks. Failed accepts
+    /// are retried after a growing pause and logged at most once per [`ACCEPT_LOG_INTERVAL`].
+    pub fn spawn(self, status: Arc<MonitorStatus>, logger: Arc<Logger>) {
+        let routes = self.routes;
+
+        tokio::spawn(async move {
+            let mut failures = 0;
+            let mut logged_at = None::<Instant>;
+
+            loop {
+                let status = Arc::clone(&status);
+
+                let accepted = match &self.listener {
+                    Listener::Tcp(listener) => listener.accept().await.map(|(stream, _)| {
+                        tokio::spawn(serve(stream, routes, status));
+                    }),
+                    #[cfg(unix)]
+                    Listener::Unix(listener) => listener.accept().await.map(|(stream, _)| {
+                        tokio::spawn(serve(stream, routes, status));
+                    }),
+                };
+
+                match accepted {
+                    Ok(()) => failures = 0,
+                    Err(err) => {
+                        if logged_at.is_none_or(|at| at.elapsed() >= ACCEPT_LOG_INTERVAL) {
+                            logged_at = Some(Instant::now());
+                            let _ = log!(
+                                logger,
+                                "Server failed to accept a connection",
+                                error = err.to_string()
+                            );
+                        }
+                        failures += 1;
+
+                        let pause = ACCEPT_RETRY_MIN
+                            .saturating_mul(2u32.saturating_pow(failures - 1))
+                            .min(ACCEPT_RETRY_MAX);
+                        tokio::time::sleep(pause).await;
+                    }
+                }
+            }
+        });
+    }
+}
+
+async fn serve<S>(stream: S, routes: &[(&'static str, Route)], status: Arc<MonitorStatus>)
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let _ = with_timeout(REQUEST_TIMEOUT, handle(stream, routes, &status)).await;
+}
+
+async fn handle<S>(
+    stream: S,
+    routes: &[(&'static str, Route)],
+    status: &MonitorStatus,
+) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await?;
+
+    // The headers are not needed, but have to be read before the response is sent.
+    let mut line = String::new();
+    for _ in 0..MAX_REQUEST_LINES {
+        line.clear();
+        if reader.read_line(&mut line).await? == 0 || line.trim().is_empty() {
+            break;
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let response = match (parts.next(), parts.next()) {
+        (Some("GET"), Some(target)) => {
+            let path = target.split('?').next().unwrap_or(target);
+
+            match routes.iter().find(|(route, _)| *route == path) {
+                Some((_, route)) => route(status).unwrap_or_else(|_| Response::error(500)),
+                None => Response::error(404),
+            }
+        }
+        (Some(_), Some(_)) => Response::error(405),
+        _ => Response::error(400),
+    };
+
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        reason(response.status),
+        response.content_type,
+        response.body.len()
+    );
+
+    let mut stream = reader.into_inner();
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(response.body.as_bytes()).await?;
+    stream.shutdown().await?;
+
+    Ok(())
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    }
+}
@@ -0,0 +1,21 @@
+use anyhow::Result;
+use serde_json::json;
+
+use crate::{server::Response, status::MonitorStatus};
+
+const CONTENT_TYPE: &str = "application/json";
+
+/// Renders the confirmed connectivity, the time of the last transition, the outage counter and the
+/// latest check as JSON.
+pub fn render(status: &MonitorStatus) -> Result<Response> {
+    let data = status.lock()?;
+
+    let body = json!({
+        "connectivity": data.connectivity,
+        "since": data.since,
+        "outages": data.outages,
+        "result": data.last_result,
+    });
+
+    Ok(Response::ok(CONTENT_TYPE, format!("{body}\n")))
+}
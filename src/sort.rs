@@ -1,10 +1,15 @@
-use std::sync::OnceLock;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
 
 use regex::Regex;
 
 static DATE_RE: OnceLock<Regex> = OnceLock::new();
+static LOGFILE_RE: OnceLock<Regex> = OnceLock::new();
 
-pub fn sort_by_filename_date<I, F>(collection: &mut Vec<I>, cb: F)
+pub fn sort_by_filename_date<I, F>(collection: &mut [I], cb: F)
 where
     F: Fn(&I) -> &str,
 {
@@ -19,3 +24,41 @@ where
 
     collection.reverse();
 }
+
+/// Prefix, date and index of a logfile named `<prefix>_<date>_<index>.jsonl`, which may be
+/// followed by the extension of its compression.
+pub fn parse_logfile_name(name: &str) -> Option<(&str, &str, u64)> {
+    let re = LOGFILE_RE.get_or_init(|| {
+        Regex::new(r"^(.*)_(\d{4}-\d{2}-\d{2})_(\d+)\.jsonl(?:\.gz|\.zst)?$").unwrap()
+    });
+    let cap = re.captures(name)?;
+    let index = cap.get(3)?.as_str().parse().ok()?;
+
+    Some((cap.get(1)?.as_str(), cap.get(2)?.as_str(), index))
+}
+
+/// The newest logfile of every prefix among `paths`, which a running monitor may still write into.
+/// Files not named like `<prefix>_<date>_<index>.jsonl` are left out.
+pub fn newest_per_prefix<'a>(paths: impl IntoIterator<Item = &'a PathBuf>) -> Vec<&'a Path> {
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let (prefix, date, index) = parse_logfile_name(path.file_name()?.to_str()?)?;
+
+            Some((
+                path.parent()?.join(prefix),
+                (date.to_string(), index),
+                path.as_path(),
+            ))
+        })
+        .fold(HashMap::new(), |mut newest, (prefix, key, path)| {
+            let entry = newest.entry(prefix).or_insert((key.clone(), path));
+            if key > entry.0 {
+                *entry = (key, path);
+            }
+            newest
+        })
+        .into_values()
+        .map(|(_, path)| path)
+        .collect()
+}
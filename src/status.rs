@@ -0,0 +1,138 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::{Mutex, MutexGuard},
+    time::Duration,
+};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+
+use crate::model::{Connectivity, InternetCheckResult};
+
+/// Upper bounds of the latency histogram buckets in seconds.
+pub const LATENCY_BUCKETS: [f64; 10] = [0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Number of latencies kept per target for the dashboard.
+pub const LATENCY_HISTORY: usize = 120;
+
+/// Number of outages kept for the dashboard.
+pub const RECENT_OUTAGES: usize = 20;
+
+/// State of the running monitor, updated after every check and read by the servers and the
+/// dashboard.
+#[derive(Default)]
+pub struct MonitorStatus(Mutex<StatusData>);
+
+#[derive(Default)]
+pub struct StatusData {
+    pub connectivity: Option<Connectivity>,
+    pub since: Option<DateTime<Utc>>,
+    pub outages: u64,
+    pub last_result: Option<InternetCheckResult>,
+    pub targets: BTreeMap<String, TargetStats>,
+    /// Latest outages first. The first one has no end while it lasts.
+    pub recent_outages: VecDeque<RecentOutage>,
+}
+
+pub struct RecentOutage {
+    pub start: DateTime<Utc>,
+    pub end: Option<DateTime<Utc>>,
+}
+
+#[derive(Default)]
+pub struct TargetStats {
+    pub up: bool,
+    pub successes: u64,
+    pub failures: u64,
+    pub errors: BTreeMap<&'static str, u64>,
+    pub latency: Histogram,
+    pub last_latency: Option<Duration>,
+    pub last_error: Option<String>,
+    /// Latencies of the latest checks in milliseconds, `None` for failed checks.
+    pub history: VecDeque<Option<u64>>,
+}
+
+/// Cumulative histogram with the `LATENCY_BUCKETS`.
+#[derive(Default)]
+pub struct Histogram {
+    pub buckets: [u64; LATENCY_BUCKETS.len()],
+    pub sum: f64,
+    pub count: u64,
+}
+
+impl MonitorStatus {
+    /// Records the check together with the connectivity confirmed by the monitor and its start.
+    pub fn record(
+        &self,
+        result: &InternetCheckResult,
+        connectivity: Connectivity,
+        since: DateTime<Utc>,
+    ) -> Result<()> {
+        let mut data = self.lock()?;
+
+        let was_offline = data.connectivity == Some(Connectivity::Offline);
+        match (was_offline, connectivity == Connectivity::Offline) {
+            (false, true) => {
+                data.outages += 1;
+                data.recent_outages.push_front(RecentOutage {
+                    start: since,
+                    end: None,
+                });
+                data.recent_outages.truncate(RECENT_OUTAGES);
+            }
+            (true, false) => {
+                if let Some(outage) = data.recent_outages.front_mut() {
+                    outage.end = Some(since);
+                }
+            }
+            _ => {}
+        }
+        data.connectivity = Some(connectivity);
+        data.since = Some(since);
+        data.last_result = Some(result.clone());
+
+        for target in result.iter_target_results() {
+            let stats = data.targets.entry(target.target().to_string()).or_default();
+            stats.up = target.success();
+            stats.last_error = target.error().map(|e| e.to_string());
+
+            if target.success() {
+                stats.successes += 1;
+                stats.latency.observe(target.latency_duration());
+                stats.last_latency = Some(*target.latency_duration());
+            } else {
+                let kind = target.error().map(|e| e.kind()).unwrap_or("other");
+
+                stats.failures += 1;
+                *stats.errors.entry(kind).or_default() += 1;
+                stats.last_latency = None;
+            }
+
+            let millis = stats.last_latency.map(|d| d.as_millis() as u64);
+            stats.history.push_back(millis);
+            if stats.history.len() > LATENCY_HISTORY {
+                stats.history.pop_front();
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn lock(&self) -> Result<MutexGuard<'_, StatusData>> {
+        self.0.lock().map_err(|_| anyhow::anyhow!("Mutex poisened"))
+    }
+}
+
+impl Histogram {
+    fn observe(&mut self, duration: &Duration) {
+        let seconds = duration.as_secs_f64();
+
+        LATENCY_BUCKETS
+            .iter()
+            .zip(self.buckets.iter_mut())
+            .filter(|(bound, _)| seconds <= **bound)
+            .for_each(|(_, bucket)| *bucket += 1);
+        self.sum += seconds;
+        self.count += 1;
+    }
+}
@@ -0,0 +1,274 @@
+use std::{
+    env,
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use directories::BaseDirs;
+
+use crate::app::service::InstallServiceArgs;
+
+/// Socket of systemd to send state changes to, if the monitor runs as a notify service.
+#[derive(Debug, Clone, Default)]
+pub struct NotifySocket(Option<OsString>);
+
+impl NotifySocket {
+    /// Takes the socket from `NOTIFY_SOCKET`. Without it, nothing is sent.
+    pub fn from_env() -> Self {
+        Self(env::var_os("NOTIFY_SOCKET"))
+    }
+
+    pub fn notify(&self, state: &str) -> Result<()> {
+        let Some(socket) = &self.0 else {
+            return Ok(());
+        };
+
+        send(socket, state.as_bytes())
+            .map_err(|err| anyhow::anyhow!("Failed to notify systemd via {socket:?}: {err}"))
+    }
+
+    /// Reports the monitor as started, together with the status text.
+    pub fn ready(&self, status: &str) -> Result<()> {
+        self.notify(&format!("READY=1\nSTATUS={status}"))
+    }
+
+    pub fn status(&self, status: &str) -> Result<()> {
+        self.notify(&format!("STATUS={status}"))
+    }
+
+    pub fn stopping(&self) -> Result<()> {
+        self.notify("STOPPING=1")
+    }
+}
+
+/// Sends a datagram to a Unix socket, which may be a path or, starting with '@', an abstract name.
+#[cfg(unix)]
+pub fn send(socket: &std::ffi::OsStr, data: &[u8]) -> std::io::Result<()> {
+    use std::os::unix::{ffi::OsStrExt, net::UnixDatagram};
+
+    let datagram = UnixDatagram::unbound()?;
+
+    // Sockets starting with '@' live in the abstract namespace.
+    match socket.as_bytes().strip_prefix(b"@") {
+        #[cfg(target_os = "linux")]
+        Some(name) => {
+            use std::os::linux::net::SocketAddrExt;
+
+            let address = std::os::unix::net::SocketAddr::from_abstract_name(name)?;
+            datagram.send_to_addr(data, &address)?;
+        }
+        _ => {
+            datagram.send_to(data, socket)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn send(_socket: &std::ffi::OsStr, _data: &[u8]) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// Watchdog that systemd expects to be pinged within `WATCHDOG_USEC`.
+pub struct Watchdog {
+    socket: NotifySocket,
+    timeout: Duration,
+    ticks: tokio::time::Interval,
+}
+
+impl Watchdog {
+    /// Creates the watchdog, if systemd enabled it for this process.
+    pub fn from_env() -> Option<Self> {
+        Self::new(
+            NotifySocket::from_env(),
+            &env::var("WATCHDOG_USEC").ok()?,
+            env::var("WATCHDOG_PID").ok().as_deref(),
+        )
+    }
+
+    /// Creates the watchdog from the values of `WATCHDOG_USEC` and `WATCHDOG_PID`, if it's enabled
+    /// for this process.
+    fn new(socket: NotifySocket, usec: &str, pid: Option<&str>) -> Option<Self> {
+        let usec = usec.parse::<u64>().ok()?;
+        let pid_matches = pid
+            .and_then(|pid| pid.parse::<u32>().ok())
+            .is_none_or(|pid| pid == std::process::id());
+
+        if usec == 0 || !pid_matches {
+            return None;
+        }
+
+        let timeout = Duration::from_micros(usec);
+        Some(Self {
+            socket,
+            timeout,
+            // Pinging twice per timeout is what sd_watchdog_enabled(3) recommends.
+            ticks: tokio::time::interval(timeout / 2),
+        })
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Waits for the next ping to be due.
+    pub async fn tick(&mut self) {
+        self.ticks.tick().await;
+    }
+
+    pub fn ping(&self) -> Result<()> {
+        self.socket.notify("WATCHDOG=1")
+    }
+}
+
+/// Writes a unit file that runs the monitor with the given arguments as a notify service.
+pub fn install(args: InstallServiceArgs, config_file: Option<&Path>) -> Result<()> {
+    let unit = unit_file(&args, config_file)?;
+
+    if args.print {
+        print!("{unit}");
+        return Ok(());
+    }
+
+    let dir = match args.system {
+        true => PathBuf::from("/etc/systemd/system"),
+        false => BaseDirs::new()
+            .ok_or(anyhow::anyhow!("Failed to find the home directory"))?
+            .config_dir()
+            .join("systemd/user"),
+    };
+    let path = dir.join(format!("{}.service", args.name));
+
+    if path.exists() && !args.force {
+        return Err(anyhow::anyhow!(
+            "The unit file '{}' already exists, use --force to overwrite it",
+            path.display()
+        ));
+    }
+
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("Failed to create directory '{}'", dir.display()))?;
+    fs::write(&path, unit)
+        .with_context(|| format!("Failed to write unit file '{}'", path.display()))?;
+
+    let systemctl = match args.system {
+        true => "sudo systemctl",
+        false => "systemctl --user",
+    };
+    println!("Wrote {}", path.display());
+    println!(
+        "Enable it with: {systemctl} daemon-reload && {systemctl} enable --now {}.service",
+        args.name
+    );
+
+    Ok(())
+}
+
+fn unit_file(args: &InstallServiceArgs, config_file: Option<&Path>) -> Result<String> {
+    let exe = env::current_exe().context("Failed to find the netcheck executable")?;
+
+    let mut command = vec![exe.to_string_lossy().to_string()];
+    if let Some(config_file) = config_file {
+        let config_file = config_file
+            .canonicalize()
+            .with_context(|| format!("Failed to find config file '{}'", config_file.display()))?;
+        command.push("--config".into());
+        command.push(config_file.to_string_lossy().to_string());
+    }
+    command.push("monitor".into());
+    command.extend(args.monitor_args.iter().cloned());
+
+    let exec_start = command
+        .iter()
+        .map(|arg| quote(arg))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let watchdog = match args.watchdog {
+        0 => String::new(),
+        seconds => format!("WatchdogSec={seconds}\n"),
+    };
+    let wanted_by = match args.system {
+        true => "multi-user.target",
+        false => "default.target",
+    };
+
+    Ok(format!(
+        "[Unit]
+Description=netcheck internet connection monitor
+Wants=network-online.target
+After=network-online.target
+
+[Service]
+Type=notify
+NotifyAccess=main
+ExecStart={exec_start}
+ExecReload=/bin/kill -HUP $MAINPID
+{watchdog}Restart=on-failure
+
+[Install]
+WantedBy={wanted_by}
+"
+    ))
+}
+
+/// Quotes an argument for `ExecStart`, which splits at whitespace and expands `%` specifiers and
+/// `$` variables.
+fn quote(arg: &str) -> String {
+    let escaped = arg
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('%', "%%")
+        .replace('$', "$$");
+
+    match arg.is_empty() || arg.contains(|c: char| c.is_whitespace() || "\"'\\".contains(c)) {
+        true => format!("\"{escaped}\""),
+        false => escaped,
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::os::unix::net::UnixDatagram;
+
+    use super::*;
+
+    fn receive(socket: &UnixDatagram) -> String {
+        let mut buffer = [0; 256];
+        let len = socket.recv(&mut buffer).unwrap();
+        String::from_utf8_lossy(&buffer[..len]).into_owned()
+    }
+
+    #[tokio::test]
+    async fn notifies_the_socket() {
+        let path = env::temp_dir().join(format!("netcheck-notify-{}.sock", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let socket = UnixDatagram::bind(&path).unwrap();
+        let notify_socket = NotifySocket(Some(path.clone().into()));
+
+        notify_socket.ready("Monitoring 2 targets").unwrap();
+        assert_eq!(receive(&socket), "READY=1\nSTATUS=Monitoring 2 targets");
+
+        notify_socket.status("Online").unwrap();
+        assert_eq!(receive(&socket), "STATUS=Online");
+
+        let pid = std::process::id().to_string();
+        let watchdog = Watchdog::new(notify_socket, "2000000", Some(&pid)).unwrap();
+        assert_eq!(watchdog.timeout(), Duration::from_secs(2));
+        watchdog.ping().unwrap();
+        assert_eq!(receive(&socket), "WATCHDOG=1");
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn watchdog_of_another_process_is_ignored() {
+        let other = (std::process::id() + 1).to_string();
+
+        assert!(Watchdog::new(NotifySocket::default(), "2000000", Some(&other)).is_none());
+        assert!(Watchdog::new(NotifySocket::default(), "0", None).is_none());
+    }
+}
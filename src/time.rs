@@ -1,3 +1,5 @@
+use std::borrow::Borrow;
+
 use chrono::{DateTime, Local, TimeDelta, Utc};
 
 use crate::model::InternetCheckResult;
@@ -11,6 +13,20 @@ pub fn timespan_string_custom(
     end: &InternetCheckResult,
     format_local_date: Option<&str>,
     format_time: Option<&str>,
+) -> String {
+    timespan_between(
+        &start.timestamp,
+        &end.timestamp,
+        format_local_date,
+        format_time,
+    )
+}
+
+pub fn timespan_between(
+    start: &DateTime<Utc>,
+    end: &DateTime<Utc>,
+    format_local_date: Option<&str>,
+    format_time: Option<&str>,
 ) -> String {
     let format_local_date_str = format_local_date.unwrap_or("%Y-%m-%d");
     let format_time_str = format_time.unwrap_or("%H:%M");
@@ -22,22 +38,18 @@ pub fn timespan_string_custom(
     };
     let to_time = |d: &DateTime<Utc>| d.with_timezone(&Local).format(format_time_str).to_string();
 
-    let date_first = to_local_date(&start.timestamp);
-    let date_current = to_local_date(&end.timestamp);
+    let date_first = to_local_date(start);
+    let date_current = to_local_date(end);
 
     if date_first == date_current {
-        format!(
-            "{date_first}: {} - {}",
-            to_time(&start.timestamp),
-            to_time(&end.timestamp)
-        )
+        format!("{date_first}: {} - {}", to_time(start), to_time(end))
     } else {
         format!(
             "{}: {} - {}: {}",
             date_first,
-            to_time(&start.timestamp),
+            to_time(start),
             date_current,
-            to_time(&end.timestamp)
+            to_time(end)
         )
     }
 }
@@ -96,3 +108,56 @@ impl Humanize for TimeDelta {
         }
     }
 }
+
+pub fn calculate_avg<I>(deltas: I) -> Option<TimeDelta>
+where
+    I: IntoIterator,
+    I::Item: Borrow<TimeDelta>,
+    I::IntoIter: ExactSizeIterator,
+{
+    let iter = deltas.into_iter();
+    let count = iter.len();
+
+    if count == 0 {
+        return None;
+    }
+
+    let total_nanos: i128 = iter
+        .map(|d| d.borrow().num_nanoseconds().unwrap_or(0) as i128)
+        .sum();
+
+    let avg_nanos = total_nanos / (count as i128);
+
+    Some(TimeDelta::nanoseconds(avg_nanos as i64))
+}
+
+/// Duration of a period from its first check to the first check after it.
+pub fn period_duration(start: &DateTime<Utc>, end: &DateTime<Utc>) -> TimeDelta {
+    *end - *start
+}
+
+/// Parses a duration like '30s', '15m', '12h', '7d' or '2w'.
+pub fn parse_duration(value: &str) -> Result<std::time::Duration, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split);
+    let amount = amount
+        .parse::<u64>()
+        .map_err(|_| format!("invalid duration '{value}', expected e.g. '7d'"))?;
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        "w" => 604800,
+        _ => return Err(format!("invalid unit '{unit}', expected s, m, h, d or w")),
+    };
+
+    let seconds = amount
+        .checked_mul(seconds)
+        .ok_or_else(|| format!("duration '{value}' is too long"))?;
+
+    Ok(std::time::Duration::from_secs(seconds))
+}
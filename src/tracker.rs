@@ -1,35 +1,189 @@
-use crate::model::{Connectivity, InternetCheckResult};
+use chrono::TimeDelta;
+
+use crate::{
+    config::DebounceConfig,
+    model::{Connectivity, InternetCheckResult, MonitorGap},
+};
 
 pub struct DowntimeTracker<'a> {
+    matches: Box<dyn Fn(&InternetCheckResult) -> bool>,
+    debouncer: Debouncer,
     first_offline: Option<&'a InternetCheckResult>,
+    candidate: Option<&'a InternetCheckResult>,
+    gaps: Vec<MonitorGap>,
+    last: Option<&'a InternetCheckResult>,
 }
 
 impl<'a> DowntimeTracker<'a> {
-    pub fn new() -> Self {
+    /// Creates a tracker for outages, which only start and end after as many consecutive failed
+    /// or successful checks as the debounce rules require.
+    pub fn for_outages(debounce: &DebounceConfig) -> Self {
+        let mut tracker = Self::for_state(Connectivity::Offline);
+        tracker.debouncer = Debouncer::new(debounce, false);
+        tracker
+    }
+
+    /// Creates a tracker for periods in the given state, e.g. `Connectivity::Degraded`.
+    pub fn for_state(state: Connectivity) -> Self {
+        Self::matching(move |result| result.connectivity() == state)
+    }
+
+    /// Creates a tracker for periods of slow checks while the connection is up. Without a
+    /// threshold, the speed rated at the time of the check is used.
+    pub fn for_slowness(latency_threshold: Option<u64>) -> Self {
+        Self::matching(move |result| {
+            result.connectivity() != Connectivity::Offline && result.is_slow(latency_threshold)
+        })
+    }
+
+    fn matching<M>(matches: M) -> Self
+    where
+        M: Fn(&InternetCheckResult) -> bool + 'static,
+    {
         Self {
+            matches: Box::new(matches),
+            debouncer: Debouncer::new(&DebounceConfig::default(), false),
             first_offline: None,
+            candidate: None,
+            gaps: vec![],
+            last: None,
         }
     }
 
+    /// Ends a period at the last check before the monitor stopped running, instead of stretching
+    /// it over the time without data.
+    pub fn with_gaps(mut self, gaps: Vec<MonitorGap>) -> Self {
+        self.gaps = gaps;
+        self
+    }
+
+    /// Calls back with the first result of a period and the first result after it. A period
+    /// starts at the first check of the streak that confirmed it and ends at the first check of
+    /// the streak that lifted it.
     pub fn track<T, F>(&mut self, result: &'a InternetCheckResult, cb: F) -> Option<T>
     where
         F: Fn(&'a InternetCheckResult, &'a InternetCheckResult) -> Option<T>,
     {
-        match (self.first_offline, result.connectivity()) {
-            (None, Connectivity::Offline) => {
-                self.first_offline = Some(result);
+        let interrupted = match self.last.replace(result) {
+            Some(last)
+                if self.gaps.iter().any(|gap| {
+                    gap.overlap(&last.timestamp, &result.timestamp) > TimeDelta::zero()
+                }) =>
+            {
+                self.interrupt(last, &cb)
+            }
+            _ => None,
+        };
+
+        let tracked = match self.debouncer.update((self.matches)(result)) {
+            DebounceStep::Pending { streak: 1, .. } => {
+                self.candidate = Some(result);
                 None
             }
-            (Some(first), Connectivity::Online) => {
-                self.first_offline = None;
+            DebounceStep::Flipped => {
+                let first = self.candidate.take().unwrap_or(result);
 
-                cb(first, result)
+                if self.debouncer.is_active() {
+                    self.first_offline = Some(first);
+                    None
+                } else {
+                    cb(self.first_offline.take()?, first)
+                }
+            }
+            DebounceStep::Interrupted => {
+                self.candidate = None;
+                None
             }
             _ => None,
-        }
+        };
+
+        interrupted.or(tracked)
+    }
+
+    fn interrupt<T, F>(&mut self, last: &'a InternetCheckResult, cb: &F) -> Option<T>
+    where
+        F: Fn(&'a InternetCheckResult, &'a InternetCheckResult) -> Option<T>,
+    {
+        self.debouncer.reset();
+        self.candidate = None;
+
+        cb(self.first_offline.take()?, last)
     }
 
     pub fn first_offline(&self) -> Option<&'a InternetCheckResult> {
         self.first_offline
     }
 }
+
+/// Flips between an inactive and an active state, e.g. online and offline, only after a streak
+/// of consecutive checks long enough for the debounce rules.
+#[derive(Debug, Clone, Copy)]
+pub struct Debouncer {
+    activate_after: u32,
+    deactivate_after: u32,
+    active: bool,
+    streak: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DebounceStep {
+    /// The check agrees with the current state.
+    Steady,
+    /// The check disagrees with the current state, but the streak is too short to flip it.
+    Pending { streak: u32, required: u32 },
+    /// The streak was long enough and flipped the state.
+    Flipped,
+    /// The check ended a streak before it could flip the state.
+    Interrupted,
+}
+
+impl Debouncer {
+    pub fn new(debounce: &DebounceConfig, active: bool) -> Self {
+        Self {
+            activate_after: debounce.failures.max(1),
+            deactivate_after: debounce.successes.max(1),
+            active,
+            streak: 0,
+        }
+    }
+
+    pub fn update(&mut self, active: bool) -> DebounceStep {
+        if active == self.active {
+            let step = match self.streak {
+                0 => DebounceStep::Steady,
+                _ => DebounceStep::Interrupted,
+            };
+            self.streak = 0;
+
+            return step;
+        }
+
+        self.streak += 1;
+        let required = match self.active {
+            true => self.deactivate_after,
+            false => self.activate_after,
+        };
+
+        if self.streak >= required {
+            self.active = active;
+            self.streak = 0;
+
+            DebounceStep::Flipped
+        } else {
+            DebounceStep::Pending {
+                streak: self.streak,
+                required,
+            }
+        }
+    }
+
+    /// Forgets the state and any streak, e.g. after a period without checks.
+    pub fn reset(&mut self) {
+        self.active = false;
+        self.streak = 0;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+}